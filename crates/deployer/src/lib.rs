@@ -0,0 +1,29 @@
+//! Deploys Foundry scripts on multiple chains, then exports and verifies the deployments.
+//!
+//! The `deployer` binary is a thin CLI over this crate. Other tools can reuse the chain registry, the parsing of
+//! broadcast files, the verification and the exports:
+//!
+//! - [`ChainRegistry`] looks up chain ids, names and explorers.
+//! - [`BroadcastFile`] reads the broadcast forge writes for a run of a script on a chain.
+//! - [`Deployer`] runs a script on multiple chains with `forge script`.
+//! - [`Verifier`] verifies the contracts of a broadcast, or any deployed [`VerifyTarget`], with `forge
+//!   verify-contract`.
+//! - [`Exporter`] exports the deployments to the SDK, reports, manifests and TypeScript deployments.
+//!
+//! The Foundry commands are run through a [`ToolchainRunner`], which is [`Forge`] unless another one is provided,
+//! e.g. a fake toolchain in tests.
+
+pub mod utils;
+
+pub use utils::{
+    batch::VerifyTarget,
+    broadcast::BroadcastFile,
+    chain_data::ChainRegistry,
+    config::Config,
+    deployer::{DeployOptions, Deployer},
+    error::Error,
+    export::Exporter,
+    foundry::FoundryConfig,
+    toolchain::{Forge, ToolOutput, ToolchainRunner},
+    verify::Verifier,
+};
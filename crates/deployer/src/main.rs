@@ -1,50 +1,136 @@
-use std::{env, fs, path::Path, process::Command, thread, time::Duration};
-use toml::Value as TomlValue;
-
-mod utils;
-use utils::{constants, verify, Broadcast};
+use deployer::{
+    utils::{
+        batch, broadcast::BroadcastRun, constants, export::ExportFormat, forge::ScriptOptions, manifest,
+        report::ReportFormat, selection, signer::Signer, typescript,
+    },
+    Config, DeployOptions, Deployer, Error, Exporter, Forge, FoundryConfig, Verifier, VerifyTarget,
+};
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+    process,
+};
 
 fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
     // Process command-line arguments
     let args: Vec<String> = env::args().collect();
-    let mut iter = args.iter().skip(1);
+
+    // The first argument can name a command to run instead of the deployment
+    let command = args.get(1).map(String::as_str).filter(|arg| ["copy", "manifest", "report", "verify"].contains(arg));
+    let mut iter = args.iter().skip(if command.is_some() { 2 } else { 1 });
 
     // Variables to store flags and provided chains
-    let mut broadcast_deployment = false;
-    let mut cp_broadcasted_file = false;
-    let mut gas_price = "".to_string();
+    let mut address = None;
+    let mut batch_path = None;
+    let mut constructor_args = Vec::new();
+    let mut excluded_chains = Vec::new();
+    let mut export_format = None;
     let mut on_all_chains = false;
+    let mut options = DeployOptions::default();
+    let mut output_path = None;
+    let mut print_deployment = false;
+    let mut profile = constants::DEFAULT_PROFILE.to_string();
+    let mut report_format = ReportFormat::default();
     let mut provided_chains = Vec::new();
-    let mut sender = "".to_string();
-    let mut script_name = "".to_string();
-    let mut show_cli = false;
-    let mut verify_deployment = false;
+    let mut script = ScriptOptions::default();
+    let mut sdk_dir = None;
+    let mut signers = Vec::new();
+    let mut verify_chain = None;
+    let mut verify_contract = None;
 
     // Parse all arguments
     while let Some(arg) = iter.next() {
         match arg.as_str() {
             "--help" => {
                 display_message();
-                return;
+                return Ok(());
+            }
+            "--account" => {
+                signers.push(Signer::Account(next_value(&mut iter, arg)?));
+            }
+            "--address" => {
+                address = Some(next_value(&mut iter, arg)?);
             }
             "--all" => on_all_chains = true,
-            "--broadcast" => broadcast_deployment = true,
-            "--cp-bf" => cp_broadcasted_file = true,
-            "--gas-price" => {
-                let value = iter.next().expect("gas price value").to_string();
-                gas_price = value.to_string();
+            "--args" => {
+                let value = next_value(&mut iter, arg)?;
+                constructor_args.extend(value.split(',').filter(|a| !a.is_empty()).map(batch::parse_arg));
+            }
+            "--batch" => {
+                batch_path = Some(next_value(&mut iter, arg)?);
+            }
+            "--broadcast" => options.broadcast = true,
+            "--chain" => {
+                verify_chain = Some(next_value(&mut iter, arg)?);
+            }
+            "--contract" => {
+                script.contract = Some(next_value(&mut iter, arg)?);
+            }
+            "--cp-bf" => options.copy_broadcast = true,
+            "--emit-script" => {
+                options.emit_script = Some(PathBuf::from(next_value(&mut iter, arg)?));
+            }
+            "--exclude" => {
+                let value = next_value(&mut iter, arg)?;
+                excluded_chains.extend(value.split(',').filter(|c| !c.is_empty()).map(String::from));
+            }
+            "--export-format" => export_format = Some(ExportFormat::parse(&next_value(&mut iter, arg)?)?),
+            "--force" => options.force = true,
+            "--fork-check" => options.fork_check = true,
+            "--format" => report_format = ReportFormat::parse(&next_value(&mut iter, arg)?)?,
+            "--gas-estimate-multiplier" | "--gas-price" | "--priority-gas-price" | "--with-gas-price" => {
+                let value = next_value(&mut iter, arg)?;
+                options.gas.set(arg, &value)?;
+            }
+            "--interactive" => signers.push(Signer::Interactive),
+            "--print-deployment" => print_deployment = true,
+            "--private-key-env" => {
+                signers.push(Signer::PrivateKeyEnv(next_value(&mut iter, arg)?));
+            }
+            "--output" => {
+                output_path = Some(next_value(&mut iter, arg)?);
+            }
+            "--profile" => {
+                profile = next_value(&mut iter, arg)?;
             }
             "--script" => {
-                script_name = iter.next().expect("script name").to_string();
+                script.name = next_value(&mut iter, arg)?;
+            }
+            "--script-dir" => {
+                script.dir = next_value(&mut iter, arg)?;
+            }
+            "--run" => {
+                options.run = Some(BroadcastRun::parse(&next_value(&mut iter, arg)?)?);
+            }
+            "--sdk-dir" => {
+                sdk_dir = Some(next_value(&mut iter, arg)?);
             }
             "--sender" => {
-                let sender_address = iter.next().expect("sender address").to_string();
-                sender = sender_address.to_string();
+                options.sender = next_value(&mut iter, arg)?;
+            }
+            "--show" => options.show_cli = true,
+            "--sig" => {
+                script.sig = Some(next_value(&mut iter, arg)?);
+            }
+            "--sig-arg" => {
+                script.sig_args.push(next_value(&mut iter, arg)?);
+            }
+            "--verify" => options.verify = true,
+            "--verify-contract" => {
+                verify_contract = Some(next_value(&mut iter, arg)?);
             }
-            "--show" => show_cli = true,
-            "--verify" => verify_deployment = true,
+            // Everything after "--" is passed as-is to "forge script"
+            "--" => script.extra_args.extend(iter.by_ref().cloned()),
             _ => {
-                if !arg.starts_with("--") && !on_all_chains {
+                if !arg.starts_with("--") {
                     provided_chains.push(arg.to_string());
                 } else {
                     println!("Unknown flag: {}", arg);
@@ -53,148 +139,167 @@ fn main() {
         }
     }
 
+    // Contracts deployed without a broadcast are verified from the flags or a batch file rather than from a script
+    let verify_targets_only =
+        command == Some("verify") && (batch_path.is_some() || verify_chain.is_some() || address.is_some());
+
     // Check if a script name was provided
-    if script_name.is_empty() {
-        println!("No script was provided");
-        return;
+    if script.name.is_empty() && !verify_targets_only {
+        return Err(Error::InvalidArgument(if command == Some("verify") {
+            "The verify command needs --script, or --chain, --address and --verify-contract, or a --batch file"
+                .to_string()
+        } else {
+            "No script was provided, pass it with --script".to_string()
+        }));
     }
 
-    let chains = get_all_chains();
-
-    if on_all_chains {
-        provided_chains = chains;
-    } else {
-        // Filter out chains that are not configured in the TOML file
-        provided_chains.retain(|chain| {
-            if chains.contains(chain) {
-                true // Keep the element in the vector
-            } else {
-                println!("Chain {} is not configured in the TOML file \n", chain);
-                false // Remove the element from the vector
-            }
-        });
+    // Only one signer can be used for the deployment
+    if signers.len() > 1 {
+        return Err(Error::InvalidArgument(
+            "Only one of --account, --private-key-env and --interactive can be provided".to_string(),
+        ));
     }
+    options.signer = signers.pop().unwrap_or_default();
 
-    // Default to "sepolia" if no chains are specified and --all is not used
-    if provided_chains.is_empty() && !on_all_chains {
-        provided_chains.push("sepolia".to_string());
+    let mut config = Config::load()?;
+    if let Some(dir) = sdk_dir {
+        config.sdk.dir = dir;
+    }
+    if let Some(format) = export_format {
+        config.sdk.format = format;
     }
 
-    // Output the list of unique chains
-    let chains_string = provided_chains.clone().join(", ");
-    println!("\nDeploying to the chains: {}\n", chains_string);
-
-    // Iterate over the provided chains and run the deployment command
-    for chain in &provided_chains {
-        let env_var = "FOUNDRY_PROFILE=optimized";
-        let command = "forge";
+    // The contract of the script and the contract to verify are selected apart, so that neither is mistaken for the
+    // other
+    if verify_targets_only && script.contract.is_some() {
+        return Err(Error::InvalidArgument(
+            "--contract selects the contract of the script, pass the contract to verify with --verify-contract"
+                .to_string(),
+        ));
+    }
+    if !verify_targets_only && verify_contract.is_some() {
+        return Err(Error::InvalidArgument(
+            "--verify-contract is only used by the verify command, along with --chain and --address".to_string(),
+        ));
+    }
 
-        let mut command_args =
-            vec!["script".to_string(), format!("script/{}", script_name), "--rpc-url".to_string(), chain.to_string()];
+    if verify_targets_only {
+        let targets = match batch_path {
+            Some(_) if verify_chain.is_some() || address.is_some() => {
+                return Err(Error::InvalidArgument("--batch can't be combined with --chain and --address".to_string()));
+            }
+            Some(path) => batch::load(Path::new(&path))?,
+            None => {
+                let (Some(chain), Some(address), Some(contract)) = (verify_chain, address, verify_contract) else {
+                    return Err(Error::InvalidArgument(
+                        "The verify command needs --chain, --address and --verify-contract, or a --batch file"
+                            .to_string(),
+                    ));
+                };
+                let target = VerifyTarget { chain, address, contract, args: constructor_args, libraries: Vec::new() };
+                target.validate()?;
+                vec![target]
+            }
+        };
+        return verify_targets(&config, &FoundryConfig::load(&profile)?, &targets, options.show_cli);
+    }
 
-        if broadcast_deployment {
-            command_args.push("--broadcast".to_string());
-        }
+    // Commands cover every chain with a broadcast when no chain is provided
+    let report_all_chains = command.is_some() && provided_chains.is_empty() && !on_all_chains;
+    if report_all_chains {
+        on_all_chains = true;
+    }
 
-        if !gas_price.is_empty() {
-            command_args.push("--gas-price".to_string());
-            command_args.push(gas_price.to_string());
-        }
+    // Fall back to the default chain only when no chain was provided at all
+    if provided_chains.is_empty() && !on_all_chains {
+        println!("No chains were provided, defaulting to {}", constants::DEFAULT_CHAIN);
+        provided_chains.push(constants::DEFAULT_CHAIN.to_string());
+    }
 
-        // Push the sender flag.
-        command_args.push("--sender".to_string());
+    let foundry = FoundryConfig::load(&profile)?;
 
-        // If no sender address was passed, use the default one.
-        if sender.is_empty() {
-            sender = constants::DEFAULT_DEPLOYER.to_string();
-        }
+    let chains = foundry.chains();
+    provided_chains =
+        selection::select_chains(&provided_chains, &excluded_chains, on_all_chains, &chains, &config.groups)?;
 
-        // Push the sender address.
-        command_args.push(sender.to_string());
+    if let Some(command) = command {
+        let chain_ids = foundry.chain_ids(&provided_chains, &Forge)?;
+        let run = options.run.clone().unwrap_or_default();
+        let exporter = Exporter::new(&config, &script.name, &chain_ids).with_run(run.clone());
 
-        // Add the legacy flag for the "chiliz", "form, and "linea" chains, due to the lack of EIP-3855 support.
-        if chain.eq("chiliz") || chain.eq("form") || chain.eq("linea") {
-            command_args.push("--legacy".to_string());
+        // Chains without a broadcast are skipped when covering every chain
+        if report_all_chains {
+            provided_chains.retain(|chain| exporter.broadcast(chain).path().exists());
+            if provided_chains.is_empty() {
+                return Err(Error::MissingBroadcasts { script: script.name.clone(), run: run.to_string() });
+            }
         }
 
-        let full_command = format!("{} {} {}", env_var, command, command_args.join(" "));
-
-        if show_cli {
-            println!("Command to be executed: {} \n", full_command);
-        } else {
-            println!("Running the deployment command: {}", full_command);
-
-            // Set the environment variable
-            let env_var_parts: Vec<&str> = env_var.split('=').collect();
-            env::set_var(env_var_parts[0], env_var_parts[1]);
-
-            // Create the CLI and capture the command output
-            let output = Command::new(command).args(&command_args).output().expect("Failed to run command");
-
-            // Process command output
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            if output.status.success() {
-                println!("Command output: {}\n", output_str);
-            } else {
-                eprintln!("Command failed with error: {}\n", String::from_utf8_lossy(&output.stderr));
+        match command {
+            "copy" => {
+                let mut failed_chains = Vec::new();
+                for chain in &provided_chains {
+                    if let Err(e) = exporter.copy(&exporter.broadcast(chain), options.force) {
+                        eprintln!("Error: {}\n", e);
+                        failed_chains.push(chain.clone());
+                    }
+                }
+                if !failed_chains.is_empty() {
+                    return Err(Error::FailedChains(failed_chains));
+                }
             }
-
-            // Initialize the `Broadcast` instance
-            let broadcast = Broadcast::new(&output_str, &script_name, broadcast_deployment)
-                .expect("Failed to create Broadcast instance");
-
-            if cp_broadcasted_file {
-                broadcast.copy_broadcast_file(chain);
+            "report" => print!("{}", exporter.report(report_format, &provided_chains, report_all_chains)?),
+            "verify" => {
+                let verifier = Verifier::new(&Forge, &foundry, &config.verify, options.show_cli);
+                let failed = verifier.verify_all(&script.name, &run, &provided_chains, &chain_ids);
+                if !failed.is_empty() {
+                    return Err(Error::FailedVerifications(failed));
+                }
+            }
+            _ => {
+                let path = PathBuf::from(output_path.unwrap_or_else(|| manifest::DEFAULT_MANIFEST_FILE.to_string()));
+                exporter.manifest(&path, &provided_chains, report_all_chains)?;
             }
         }
+        return Ok(());
     }
 
-    // If the verify flag is set, run the verification process
-    if verify_deployment {
-        if !show_cli {
-            println!("Waiting for 10 seconds to allow explorer to process deployments... \n");
-            thread::sleep(Duration::from_secs(10)); // Sleep for 10 seconds
-        }
-        verify::verify_contracts(&script_name, &provided_chains, show_cli);
+    if print_deployment {
+        options.print_deployment =
+            Some(PathBuf::from(output_path.unwrap_or_else(|| typescript::DEFAULT_DEPLOYMENTS_FILE.to_string())));
     }
-}
 
-// Function to display the help message
-pub fn display_message() {
-    println!("{}", constants::HELP_MESSAGE);
+    Deployer::new(config, foundry, script, options).deploy(&provided_chains)
 }
 
-// Function that reads the TOML chain configurations and extracts them
-fn get_all_chains() -> Vec<String> {
-    // Define the path to the TOML file
-    let toml_path = Path::new("foundry.toml");
-
-    // Read and parse the TOML file content
-    let toml_content = match fs::read_to_string(toml_path) {
-        Ok(content) => content,
-        Err(_) => {
-            eprintln!("Failed to read the TOML file\n");
-            return Vec::new();
-        }
-    };
-
-    let toml_values: TomlValue = match toml::from_str(&toml_content) {
-        Ok(value) => value,
-        Err(_) => {
-            eprintln!("Failed to parse TOML content\n");
-            return Vec::new();
+// Verifies contracts deployed without a broadcast, on chains declared in `foundry.toml`.
+fn verify_targets(
+    config: &Config,
+    foundry: &FoundryConfig,
+    targets: &[VerifyTarget],
+    show_cli: bool,
+) -> Result<(), Error> {
+    let mut chains: Vec<String> = Vec::new();
+    for target in targets {
+        if !chains.contains(&target.chain) {
+            chains.push(target.chain.clone());
         }
-    };
+    }
+    let chains = selection::select_chains(&chains, &[], false, &foundry.chains(), &HashMap::new())?;
 
-    // Extract chains from the TOML data
-    let sections = ["rpc_endpoints"];
-    let mut chains = Vec::new();
+    let chain_ids = foundry.chain_ids(&chains, &Forge)?;
+    Verifier::new(&Forge, foundry, &config.verify, show_cli).verify_targets(targets, &chain_ids)
+}
 
-    for section in &sections {
-        if let Some(table) = toml_values.get(section).and_then(|v| v.as_table()) {
-            chains.extend(table.keys().filter(|&key| key != "localhost").cloned());
-        }
-    }
+// Returns the value following a flag.
+fn next_value<'a>(
+    iter: &mut impl Iterator<Item = &'a String>,
+    flag: &str,
+) -> Result<String, Error> {
+    iter.next().cloned().ok_or_else(|| Error::MissingFlagValue { flag: flag.to_string() })
+}
 
-    chains.into_iter().collect()
+// Function to display the help message
+pub fn display_message() {
+    println!("{}", constants::HELP_MESSAGE);
 }
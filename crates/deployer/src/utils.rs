@@ -1,5 +1,23 @@
+pub mod batch;
 pub mod broadcast;
-pub use broadcast::Broadcast;
 pub mod chain_data;
+pub mod config;
 pub mod constants;
+pub mod deployer;
+pub mod diff;
+pub mod error;
+pub mod export;
+pub mod forge;
+pub mod foundry;
+pub mod gas;
+pub mod manifest;
+pub mod naming;
+pub mod package;
+pub mod rate_limit;
+pub mod report;
+pub mod selection;
+pub mod shell;
+pub mod signer;
+pub mod toolchain;
+pub mod typescript;
 pub mod verify;
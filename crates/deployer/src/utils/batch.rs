@@ -0,0 +1,160 @@
+use super::{error::Error, verify::VerifyData};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fs, io::ErrorKind, path::Path};
+
+/// A deployed contract to verify without a broadcast, as given on the command line or in a batch file.
+#[derive(Debug, Deserialize)]
+pub struct VerifyTarget {
+    /// The chain, as declared in `foundry.toml`.
+    pub chain: String,
+    /// The address of the contract.
+    pub address: String,
+    /// The name of the contract, e.g. `SablierLockup`, or its fully qualified name, e.g.
+    /// `src/SablierLockup.sol:SablierLockup`.
+    pub contract: String,
+    /// The constructor arguments, as addresses or unsigned integers.
+    #[serde(default)]
+    pub args: Vec<Value>,
+    /// The linked libraries, as `<path>:<name>:<address>`.
+    #[serde(default)]
+    pub libraries: Vec<String>,
+}
+
+impl VerifyTarget {
+    /// Checks that the address is a 20-byte hex address.
+    pub fn validate(&self) -> Result<(), Error> {
+        let is_address = self
+            .address
+            .strip_prefix("0x")
+            .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        if !is_address {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid address {} for {} on {}, expected 0x followed by 40 hex characters",
+                self.address, self.contract, self.chain
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the contract as verified by the `Verifier`.
+    pub fn verify_data(&self) -> VerifyData {
+        VerifyData {
+            contract_name: Some(self.contract.clone()),
+            contract_address: Some(self.address.clone()),
+            arguments: Some(self.args.clone()).filter(|args| !args.is_empty()),
+            libraries: Some(self.libraries.clone()).filter(|libraries| !libraries.is_empty()),
+        }
+    }
+}
+
+/// Parses a constructor argument given as text, which is an unsigned integer when it only has digits and an address
+/// otherwise. Integers larger than a `u64` are kept as strings of digits, which are encoded as a `uint256`.
+pub fn parse_arg(arg: &str) -> Value {
+    match arg.parse::<u64>() {
+        Ok(number) => Value::from(number),
+        Err(_) => Value::from(arg),
+    }
+}
+
+/// Reads the contracts to verify from a batch file, in JSON when its extension is `.json` and in CSV otherwise.
+///
+/// A JSON batch is an array of objects with the `chain`, `address` and `contract` fields, and optionally `args` and
+/// `libraries`. A CSV batch has a `chain,address,contract` line per contract, followed by the constructor arguments in
+/// the next columns. Blank lines, lines starting with `#` and a header line starting with `chain` are skipped.
+pub fn load(path: &Path) -> Result<Vec<VerifyTarget>, Error> {
+    let content = fs::read_to_string(path).map_err(|source| match source.kind() {
+        ErrorKind::NotFound => Error::MissingFile {
+            path: path.to_path_buf(),
+            hint: "pass a CSV or JSON file listing the contracts to verify".to_string(),
+        },
+        _ => Error::Io { path: path.to_path_buf(), source },
+    })?;
+
+    let targets = if path.extension().is_some_and(|extension| extension == "json") {
+        serde_json::from_str(&content).map_err(|e| Error::Parse { path: path.to_path_buf(), message: e.to_string() })?
+    } else {
+        parse_csv(&content, path)?
+    };
+
+    for target in &targets {
+        target.validate()?;
+    }
+    Ok(targets)
+}
+
+// Parses the lines of a CSV batch.
+fn parse_csv(
+    content: &str,
+    path: &Path,
+) -> Result<Vec<VerifyTarget>, Error> {
+    let mut targets = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (index == 0 && line.starts_with("chain")) {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [chain, address, contract, args @ ..] = columns.as_slice() else {
+            return Err(Error::Parse {
+                path: path.to_path_buf(),
+                message: format!("line {} has fewer than the 3 columns chain,address,contract", index + 1),
+            });
+        };
+
+        targets.push(VerifyTarget {
+            chain: chain.to_string(),
+            address: address.to_string(),
+            contract: contract.to_string(),
+            args: args.iter().filter(|arg| !arg.is_empty()).map(|arg| parse_arg(arg)).collect(),
+            libraries: Vec::new(),
+        });
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKUP: &str = "0xcf8ce57fa442ba50acbc57147a62ad03873ffa73";
+
+    #[test]
+    fn parses_the_csv_lines_with_their_arguments() {
+        let csv = format!(
+            "chain,address,contract,args\n\n# Deployed by hand\nsepolia,{0},SablierLockup\narbitrum, {0} ,Batch,{0},5,\n",
+            LOCKUP
+        );
+        let targets = parse_csv(&csv, Path::new("contracts.csv")).unwrap();
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!((targets[0].chain.as_str(), targets[0].contract.as_str()), ("sepolia", "SablierLockup"));
+        assert!(targets[0].args.is_empty());
+        assert_eq!(targets[1].address, LOCKUP);
+        assert_eq!(targets[1].args, [Value::from(LOCKUP), Value::from(5u64)]);
+    }
+
+    #[test]
+    fn only_skips_a_header_on_the_first_line() {
+        let csv = format!("sepolia,{},SablierLockup\nchain,{},Batch\n", LOCKUP, LOCKUP);
+        let targets = parse_csv(&csv, Path::new("contracts.csv")).unwrap();
+        assert_eq!(targets.iter().map(|target| target.chain.as_str()).collect::<Vec<_>>(), ["sepolia", "chain"]);
+    }
+
+    #[test]
+    fn rejects_a_line_with_fewer_than_3_columns() {
+        let csv = format!("sepolia,{},SablierLockup\narbitrum,{}\n", LOCKUP, LOCKUP);
+        match parse_csv(&csv, Path::new("contracts.csv")) {
+            Err(Error::Parse { message, .. }) => assert!(message.starts_with("line 2 "), "{}", message),
+            other => panic!("expected the line to be rejected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_integers_and_addresses() {
+        assert_eq!(parse_arg("5"), Value::from(5u64));
+        assert_eq!(parse_arg(LOCKUP), Value::from(LOCKUP));
+        assert_eq!(parse_arg("1000000000000000000000000"), Value::from("1000000000000000000000000"));
+    }
+}
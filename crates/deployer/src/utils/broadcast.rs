@@ -1,69 +1,422 @@
+use super::{
+    diff,
+    error::Error,
+    export::{ExportFormat, SlimBroadcast},
+    package::Package,
+};
+use serde::Deserialize;
 use serde_json::Value;
-use std::{fs, path::Path};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-pub struct Broadcast {
-    file_path: String,
-    project: String,
-    version: String,
+// The directory forge writes broadcasts to, relative to the project root.
+const DEFAULT_BROADCAST_DIR: &str = "broadcast";
+
+/// Where broadcast files are copied to in the SDK repository.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SdkSettings {
+    /// The root of the SDK repository.
+    pub dir: String,
+    /// The destination of a broadcast file within the SDK, where `{project}`, `{version}` and `{chain}` are replaced.
+    pub path: String,
+    /// Whether the broadcast files are copied as-is or slimmed down.
+    pub format: ExportFormat,
+}
+
+impl Default for SdkSettings {
+    fn default() -> Self {
+        SdkSettings {
+            dir: "../sdk".to_string(),
+            path: "deployments/{project}/v{version}/broadcasts/{chain}.json".to_string(),
+            format: ExportFormat::Full,
+        }
+    }
+}
+
+/// The run of a script a broadcast file is read from, for the verification and the exports.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum BroadcastRun {
+    /// The latest broadcasted run, `run-latest.json`.
+    #[default]
+    Latest,
+    /// The latest dry run, `dry-run/run-latest.json`.
+    DryRun,
+    /// A past broadcasted run, `run-<timestamp>.json`.
+    Timestamp(u64),
+    /// A broadcast file at another path, where `{chain}` and `{chain_id}` are replaced.
+    Path(String),
 }
 
-impl Broadcast {
-    // Creates a new `Broadcast` instance, initializing all fields
+impl BroadcastRun {
+    /// Parses a run selector: `latest`, `dry-run`, `run-<timestamp>.json` or a path.
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        let timestamp = value.strip_prefix("run-").and_then(|rest| rest.strip_suffix(".json"));
+        match value {
+            "" => Err(Error::InvalidArgument(
+                "The run can't be empty, expected latest, dry-run, run-<timestamp>.json or a path".to_string(),
+            )),
+            "latest" => Ok(BroadcastRun::Latest),
+            "dry-run" => Ok(BroadcastRun::DryRun),
+            _ => match timestamp.map(str::parse) {
+                Some(Ok(timestamp)) => Ok(BroadcastRun::Timestamp(timestamp)),
+                _ => Ok(BroadcastRun::Path(value.to_string())),
+            },
+        }
+    }
+
+    /// Selects the latest run, broadcasted or dry.
+    pub fn latest(is_broadcast_deployment: bool) -> Self {
+        if is_broadcast_deployment {
+            BroadcastRun::Latest
+        } else {
+            BroadcastRun::DryRun
+        }
+    }
+}
+
+impl fmt::Display for BroadcastRun {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            BroadcastRun::Latest => write!(f, "latest"),
+            BroadcastRun::DryRun => write!(f, "dry-run"),
+            BroadcastRun::Timestamp(timestamp) => write!(f, "run-{}.json", timestamp),
+            BroadcastRun::Path(path) => write!(f, "{}", path),
+        }
+    }
+}
+
+/// The broadcast file forge writes for a run of a script on a chain, the latest one unless another run is selected.
+pub struct BroadcastFile {
+    dir: PathBuf,
+    file_path: PathBuf,
+    chain: String,
+    chain_id: u64,
+    run: BroadcastRun,
+    script_name: String,
+}
+
+impl BroadcastFile {
+    /// Creates a new `BroadcastFile` instance for the latest run of a script on a chain, broadcasted or dry.
     pub fn new(
-        output_str: &str,
         script_name: &str,
+        chain: &str,
+        chain_id: u64,
         is_broadcast_deployment: bool,
-    ) -> Option<Self> {
-        // Extract the chain ID
-        let chain_id = output_str
-            .lines()
-            .find(|line| line.trim().starts_with("Chain "))
-            .and_then(|line| line.split_whitespace().nth(1))
-            .unwrap_or("")
-            .to_string();
-
-        // Determine the project name
-        let project = if script_name.contains("Protocol") || script_name.contains("Lockup") {
-            "lockup".to_string()
-        } else if script_name.contains("Flow") {
-            "flow".to_string()
-        } else if script_name.contains("Merkle") || script_name.contains("Factories") {
-            "airdrops".to_string()
-        } else {
-            // Return None if the script name doesn't match any of the above
-            return None;
-        };
+    ) -> Self {
+        let run = BroadcastRun::latest(is_broadcast_deployment);
+        BroadcastFile {
+            dir: PathBuf::from(DEFAULT_BROADCAST_DIR),
+            file_path: file_path(Path::new(DEFAULT_BROADCAST_DIR), script_name, chain, chain_id, &run),
+            chain: chain.to_string(),
+            chain_id,
+            run,
+            script_name: script_name.to_string(),
+        }
+    }
 
-        // Read the version from package.json
-        let version = serde_json::from_str::<Value>(&fs::read_to_string("package.json").unwrap()).unwrap()["version"]
-            .as_str()
-            .unwrap()
-            .to_string();
+    /// Looks for the broadcast in another directory than `broadcast`, as set with `FOUNDRY_BROADCAST`.
+    pub fn in_dir(
+        mut self,
+        dir: &Path,
+    ) -> Self {
+        self.file_path = file_path(dir, &self.script_name, &self.chain, self.chain_id, &self.run);
+        self.dir = dir.to_path_buf();
+        self
+    }
 
-        let file_path = if is_broadcast_deployment {
-            format!("broadcast/{}/{}/run-latest.json", script_name, chain_id)
-        } else {
-            format!("broadcast/{}/{}/dry-run/run-latest.json", script_name, chain_id)
+    /// Reads another run of the script than the latest one.
+    pub fn with_run(
+        mut self,
+        run: &BroadcastRun,
+    ) -> Self {
+        self.file_path = file_path(&self.dir, &self.script_name, &self.chain, self.chain_id, run);
+        self.run = run.clone();
+        self
+    }
+
+    /// Returns where forge writes the broadcast.
+    pub fn path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Returns the modification times of the broadcasts of the script on every chain, to be taken before running the
+    /// script and passed to `check_chain_id`.
+    pub fn modified_times(&self) -> HashMap<u64, SystemTime> {
+        let script_dir = self.dir.join(&self.script_name);
+        let Ok(entries) = fs::read_dir(&script_dir) else {
+            return HashMap::new();
         };
 
-        Some(Broadcast { project, version, file_path })
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str().and_then(|name| name.parse::<u64>().ok()))
+            .filter_map(|chain_id| {
+                let path = file_path(&self.dir, &self.script_name, &self.chain, chain_id, &self.run);
+                fs::metadata(path).and_then(|metadata| metadata.modified()).ok().map(|time| (chain_id, time))
+            })
+            .collect()
     }
 
-    // Copy the broadcast file to the specified destination path
+    /// Checks that the script run wrote its broadcast for the expected chain, given the modification times of the
+    /// broadcasts before the run. Forge names the directory of a broadcast after the chain id returned by the RPC, so a
+    /// broadcast written in the directory of another chain id means the RPC URL points to another network.
+    ///
+    /// Only the broadcasts written by the run are considered, so that the broadcast of an earlier run, left in the
+    /// directory of the expected chain, doesn't hide the one written for another chain id. Returns whether the run
+    /// wrote the expected broadcast, which it doesn't when the script sent no transaction.
+    pub fn check_chain_id(
+        &self,
+        before: &HashMap<u64, SystemTime>,
+    ) -> Result<bool, Error> {
+        let after = self.modified_times();
+        let is_written = |chain_id: &u64| after.get(chain_id).is_some_and(|time| before.get(chain_id) != Some(time));
+
+        if let Some(actual) = after.keys().filter(|chain_id| **chain_id != self.chain_id && is_written(chain_id)).min()
+        {
+            return Err(Error::ChainIdMismatch {
+                path: file_path(&self.dir, &self.script_name, &self.chain, *actual, &self.run),
+                chain: self.chain.clone(),
+                expected: self.chain_id,
+                actual: *actual,
+            });
+        }
+
+        if is_written(&self.chain_id) {
+            return self.read().map(|_| true);
+        }
+        Ok(false)
+    }
+
+    /// Reads the broadcast file, making sure it was written for the expected chain. Returns the content as written by
+    /// forge along with its parsed JSON.
+    pub fn read(&self) -> Result<(String, Value), Error> {
+        let content = fs::read_to_string(&self.file_path).map_err(|source| match source.kind() {
+            ErrorKind::NotFound => Error::MissingFile {
+                path: self.file_path.clone(),
+                hint: "check that the script ran and wrote its broadcast for this chain".to_string(),
+            },
+            _ => Error::Io { path: self.file_path.clone(), source },
+        })?;
+
+        let parse_error = |message: String| Error::Parse { path: self.file_path.clone(), message };
+        let json: Value = serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?;
+        let actual = json
+            .get("chain")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| parse_error("missing the \"chain\" field".to_string()))?;
+        if actual != self.chain_id {
+            return Err(Error::ChainIdMismatch {
+                path: self.file_path.clone(),
+                chain: self.chain.clone(),
+                expected: self.chain_id,
+                actual,
+            });
+        }
+
+        Ok((content, json))
+    }
+
+    /// Copies the broadcast file to its destination in the SDK, in the configured format.
+    ///
+    /// The file is written atomically. An existing destination with a different content is only overwritten when
+    /// `force` is set; otherwise the differences are printed and an error is returned.
     pub fn copy_broadcast_file(
         &self,
-        chain: &str,
-    ) {
-        let dest_path = format!("../sdk/{}/v{}/broadcasts/{}.json", self.project, self.version, chain);
-
-        // Ensure the parent directory exists
-        if let Some(parent) = Path::new(&dest_path).parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent).expect("Failed to create directories");
-            }
+        sdk: &SdkSettings,
+        projects: &HashMap<String, String>,
+        force: bool,
+    ) -> Result<(), Error> {
+        let package = Package::load()?;
+        let project = package.project(&self.script_name, projects)?;
+
+        let relative_path = sdk
+            .path
+            .replace("{project}", &project)
+            .replace("{version}", &package.version)
+            .replace("{chain}", &self.chain);
+        let dest_path = PathBuf::from(&sdk.dir).join(relative_path);
+
+        let content = sdk.format.render(&self.read()?.0, &self.file_path)?;
+        write_copy(&dest_path, &content, force)
+    }
+}
+
+// Writes the copy of a broadcast file to its destination, unless it is already up to date. An existing destination
+// with a different content is only overwritten when `force` is set; otherwise the differences are printed and an error
+// is returned.
+fn write_copy(
+    dest_path: &Path,
+    content: &str,
+    force: bool,
+) -> Result<(), Error> {
+    match fs::read_to_string(dest_path) {
+        Ok(existing) if existing == content => {
+            println!("The broadcast file {} is already up to date\n", dest_path.display());
+            return Ok(());
+        }
+        Ok(existing) if !force => {
+            println!("{}\n", diff::diff_lines(&existing, content));
+            return Err(Error::DestinationExists { path: dest_path.to_path_buf() });
+        }
+        Ok(_) => {}
+        Err(source) if source.kind() == ErrorKind::NotFound => {}
+        Err(source) => return Err(Error::Io { path: dest_path.to_path_buf(), source }),
+    }
+
+    write_atomically(dest_path, content)?;
+
+    println!("Copied the broadcast file to {}\n", dest_path.display());
+    Ok(())
+}
+
+// Returns where forge writes the broadcast of a run of a script on a chain.
+fn file_path(
+    dir: &Path,
+    script_name: &str,
+    chain: &str,
+    chain_id: u64,
+    run: &BroadcastRun,
+) -> PathBuf {
+    let chain_dir = dir.join(script_name).join(chain_id.to_string());
+    match run {
+        BroadcastRun::Latest => chain_dir.join("run-latest.json"),
+        BroadcastRun::DryRun => chain_dir.join("dry-run").join("run-latest.json"),
+        BroadcastRun::Timestamp(timestamp) => chain_dir.join(format!("run-{}.json", timestamp)),
+        BroadcastRun::Path(path) => {
+            PathBuf::from(path.replace("{chain}", chain).replace("{chain_id}", &chain_id.to_string()))
         }
+    }
+}
+
+/// Reads the broadcasts of a run of a script on the given chains and returns their deployments, along with the chain
+/// and its id. Chains without a broadcast are skipped when `skip_missing` is set; otherwise they are an error.
+pub fn read_deployments(
+    script_name: &str,
+    run: &BroadcastRun,
+    chains: &[String],
+    chain_ids: &HashMap<String, u64>,
+    skip_missing: bool,
+) -> Result<Vec<(String, u64, SlimBroadcast)>, Error> {
+    let mut deployments = Vec::new();
+    for chain in chains {
+        let chain_id = chain_ids[chain];
+        match BroadcastFile::new(script_name, chain, chain_id, true).with_run(run).read() {
+            Ok((_, json)) => deployments.push((chain.clone(), chain_id, SlimBroadcast::from_json(&json))),
+            Err(Error::MissingFile { .. }) if skip_missing => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if deployments.is_empty() {
+        return Err(Error::MissingBroadcasts { script: script_name.to_string(), run: run.to_string() });
+    }
+
+    Ok(deployments)
+}
+
+/// Writes a file atomically, by writing a temporary file next to it and moving it in place. The parent directories
+/// are created as needed.
+pub fn write_atomically(
+    path: &Path,
+    content: &str,
+) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|source| Error::Io { path: parent.to_path_buf(), source })?;
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    fs::write(&tmp_path, content).map_err(|source| Error::Io { path: tmp_path.clone(), source })?;
+    fs::rename(&tmp_path, path).map_err(|source| {
+        let _ = fs::remove_file(&tmp_path);
+        Error::Io { path: path.to_path_buf(), source }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    // A directory of its own for each test, as the tests run concurrently.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("deployer-broadcast-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn leaves_an_up_to_date_copy_as_is() {
+        let dir = test_dir("up-to-date");
+        let dest_path = dir.join("sepolia.json");
+        fs::write(&dest_path, "{\"chain\":11155111}\n").unwrap();
+        let modified = fs::metadata(&dest_path).unwrap().modified().unwrap();
+
+        write_copy(&dest_path, "{\"chain\":11155111}\n", false).unwrap();
+
+        assert_eq!(fs::metadata(&dest_path).unwrap().modified().unwrap(), modified);
+        assert!(!dir.join("sepolia.json.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_to_overwrite_a_different_copy_without_force() {
+        let dir = test_dir("different");
+        let dest_path = dir.join("deployments/sepolia.json");
+        fs::create_dir_all(dest_path.parent().unwrap()).unwrap();
+        fs::write(&dest_path, "old\n").unwrap();
+
+        let error = write_copy(&dest_path, "new\n", false).unwrap_err();
+
+        assert!(matches!(error, Error::DestinationExists { ref path } if *path == dest_path), "{:?}", error);
+        assert_eq!(fs::read_to_string(&dest_path).unwrap(), "old\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn overwrites_a_different_copy_with_force() {
+        let dir = test_dir("force");
+        let dest_path = dir.join("sepolia.json");
+        fs::write(&dest_path, "old\n").unwrap();
+
+        write_copy(&dest_path, "new\n", true).unwrap();
+
+        assert_eq!(fs::read_to_string(&dest_path).unwrap(), "new\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn creates_a_missing_copy_and_its_directories() {
+        let dir = test_dir("missing");
+        let dest_path = dir.join("deployments/lockup/v2.0.0/sepolia.json");
+
+        write_copy(&dest_path, "new\n", false).unwrap();
+
+        assert_eq!(fs::read_to_string(&dest_path).unwrap(), "new\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_a_destination_that_cant_be_read() {
+        let dir = test_dir("unreadable");
+        let dest_path = dir.join("sepolia.json");
+        fs::create_dir_all(&dest_path).unwrap();
+
+        let error = write_copy(&dest_path, "new\n", true).unwrap_err();
 
-        // Copy the file
-        fs::copy(&self.file_path, &dest_path).expect("Failed to copy and rename run-latest.json to sdk");
+        assert!(matches!(error, Error::Io { ref path, .. } if *path == dest_path), "{:?}", error);
+        assert!(dest_path.is_dir());
+        fs::remove_dir_all(dir).unwrap();
     }
 }
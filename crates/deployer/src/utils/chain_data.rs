@@ -1,67 +1,119 @@
+/// The registry data of a chain.
+#[derive(Debug)]
 pub struct ChainData {
     pub explorer_url: &'static str,
+    pub is_testnet: bool,
     pub name: &'static str,
 }
 
 const CHAINS: &[(&str, ChainData)] = &[
     // Mainnets
-    ("1", ChainData { name: "Ethereum", explorer_url: "https://etherscan.io/" }),
-    ("2741", ChainData { name: "Abstract", explorer_url: "https://abscan.org/" }),
-    ("42161", ChainData { name: "Arbitrum", explorer_url: "https://arbiscan.io/" }),
-    ("43114", ChainData { name: "Avalanche", explorer_url: "https://snowtrace.io/" }),
-    ("8453", ChainData { name: "Base", explorer_url: "https://basescan.org/" }),
-    ("80094", ChainData { name: "Berachain", explorer_url: "https://berascan.com/" }),
-    ("81457", ChainData { name: "Blast", explorer_url: "https://blastscan.io/" }),
-    ("56", ChainData { name: "BSC", explorer_url: "https://bscscan.com/" }),
-    ("88888", ChainData { name: "Chiliz", explorer_url: "https://chiliscan.com/" }),
-    ("1116", ChainData { name: "Core DAO", explorer_url: "https://scan.coredao.org/" }),
-    ("100", ChainData { name: "Gnosis", explorer_url: "https://gnosisscan.io/" }),
-    ("999", ChainData { name: "HyperEVM", explorer_url: "https://hyperevm.io/" }),
-    ("1890", ChainData { name: "Lightlink", explorer_url: "https://phoenix.lightlink.io/" }),
-    ("59144", ChainData { name: "Linea", explorer_url: "https://lineascan.build/" }),
-    ("34443", ChainData { name: "Mode", explorer_url: "https://explorer.mode.network/" }),
-    ("2818", ChainData { name: "Morph", explorer_url: "https://explorer.morphl2.io/" }),
-    ("10", ChainData { name: "Optimism", explorer_url: "https://optimistic.etherscan.io/" }),
-    ("137", ChainData { name: "Polygon", explorer_url: "https://polygonscan.com/" }),
-    ("534352", ChainData { name: "Scroll", explorer_url: "https://scrollscan.com/" }),
-    ("1329", ChainData { name: "Sei", explorer_url: "https://seistream.app/" }),
-    ("146", ChainData { name: "Sonic", explorer_url: "https://sonicscan.org/" }),
-    ("50104", ChainData { name: "Sophon", explorer_url: "https://explorer.sophon.xyz/" }),
-    ("5330", ChainData { name: "Superseed", explorer_url: "https://explorer.superseed.xyz/" }),
-    ("5845", ChainData { name: "Tangle", explorer_url: "https://explorer.tangle.tools/" }),
-    ("130", ChainData { name: "Unichain", explorer_url: "https://unichain-sepolia.blockscout.com/" }),
-    ("50", ChainData { name: "XDC", explorer_url: "https://xdcscan.com/" }),
-    ("324", ChainData { name: "zkSync", explorer_url: "https://era.zksync.network/" }),
+    ("1", ChainData { name: "Ethereum", explorer_url: "https://etherscan.io/", is_testnet: false }),
+    ("2741", ChainData { name: "Abstract", explorer_url: "https://abscan.org/", is_testnet: false }),
+    ("42161", ChainData { name: "Arbitrum", explorer_url: "https://arbiscan.io/", is_testnet: false }),
+    ("43114", ChainData { name: "Avalanche", explorer_url: "https://snowtrace.io/", is_testnet: false }),
+    ("8453", ChainData { name: "Base", explorer_url: "https://basescan.org/", is_testnet: false }),
+    ("80094", ChainData { name: "Berachain", explorer_url: "https://berascan.com/", is_testnet: false }),
+    ("81457", ChainData { name: "Blast", explorer_url: "https://blastscan.io/", is_testnet: false }),
+    ("56", ChainData { name: "BSC", explorer_url: "https://bscscan.com/", is_testnet: false }),
+    ("88888", ChainData { name: "Chiliz", explorer_url: "https://chiliscan.com/", is_testnet: false }),
+    ("1116", ChainData { name: "Core DAO", explorer_url: "https://scan.coredao.org/", is_testnet: false }),
+    ("100", ChainData { name: "Gnosis", explorer_url: "https://gnosisscan.io/", is_testnet: false }),
+    ("999", ChainData { name: "HyperEVM", explorer_url: "https://hyperevm.io/", is_testnet: false }),
+    ("1890", ChainData { name: "Lightlink", explorer_url: "https://phoenix.lightlink.io/", is_testnet: false }),
+    ("59144", ChainData { name: "Linea", explorer_url: "https://lineascan.build/", is_testnet: false }),
+    ("34443", ChainData { name: "Mode", explorer_url: "https://explorer.mode.network/", is_testnet: false }),
+    ("2818", ChainData { name: "Morph", explorer_url: "https://explorer.morphl2.io/", is_testnet: false }),
+    ("10", ChainData { name: "Optimism", explorer_url: "https://optimistic.etherscan.io/", is_testnet: false }),
+    ("137", ChainData { name: "Polygon", explorer_url: "https://polygonscan.com/", is_testnet: false }),
+    ("534352", ChainData { name: "Scroll", explorer_url: "https://scrollscan.com/", is_testnet: false }),
+    ("1329", ChainData { name: "Sei", explorer_url: "https://seistream.app/", is_testnet: false }),
+    ("146", ChainData { name: "Sonic", explorer_url: "https://sonicscan.org/", is_testnet: false }),
+    ("50104", ChainData { name: "Sophon", explorer_url: "https://explorer.sophon.xyz/", is_testnet: false }),
+    ("5330", ChainData { name: "Superseed", explorer_url: "https://explorer.superseed.xyz/", is_testnet: false }),
+    ("5845", ChainData { name: "Tangle", explorer_url: "https://explorer.tangle.tools/", is_testnet: false }),
+    (
+        "130",
+        ChainData { name: "Unichain", explorer_url: "https://unichain-sepolia.blockscout.com/", is_testnet: false },
+    ),
+    ("50", ChainData { name: "XDC", explorer_url: "https://xdcscan.com/", is_testnet: false }),
+    ("324", ChainData { name: "zkSync", explorer_url: "https://era.zksync.network/", is_testnet: false }),
     // Testnets
-    ("11155111", ChainData { name: "Sepolia", explorer_url: "https://sepolia.etherscan.io/" }),
-    ("421614", ChainData { name: "Arbitrum Sepolia", explorer_url: "https://sepolia.arbiscan.io/" }),
-    ("84532", ChainData { name: "Base Sepolia", explorer_url: "https://sepolia.basescan.org/" }),
-    ("919", ChainData { name: "Mode Sepolia", explorer_url: "https://sepolia.explorer.mode.network/" }),
-    ("11155420", ChainData { name: "Optimism Sepolia", explorer_url: "https://sepolia-optimism.etherscan.io/" }),
+    ("11155111", ChainData { name: "Sepolia", explorer_url: "https://sepolia.etherscan.io/", is_testnet: true }),
+    ("421614", ChainData { name: "Arbitrum Sepolia", explorer_url: "https://sepolia.arbiscan.io/", is_testnet: true }),
+    ("84532", ChainData { name: "Base Sepolia", explorer_url: "https://sepolia.basescan.org/", is_testnet: true }),
+    (
+        "919",
+        ChainData { name: "Mode Sepolia", explorer_url: "https://sepolia.explorer.mode.network/", is_testnet: true },
+    ),
+    (
+        "11155420",
+        ChainData {
+            name: "Optimism Sepolia",
+            explorer_url: "https://sepolia-optimism.etherscan.io/",
+            is_testnet: true,
+        },
+    ),
 ];
 
-/// Returns the chain id for a given partial or complete chain name.
-/// The search is performed in a case-insensitive manner. If a match is found (i.e. the chain's
-/// name contains the provided query), the corresponding chain id is returned.
-/// Otherwise, it returns the provided chain_name.
-pub fn get_chain_id(chain_name: &str) -> &str {
-    let query = chain_name.to_lowercase();
-    CHAINS
-        .iter()
-        .find(|(_, data)| data.name.to_lowercase().contains(&query))
-        .map(|(chain_id, _)| *chain_id)
-        .unwrap_or(chain_name)
-}
+// The zkEVM chains, which run zksolc bytecode and are deployed to with foundry-zksync, along with the endpoint of their
+// zkSync verifier. Their testnets are listed although the registry doesn't name them.
+const ZKEVM_CHAINS: &[(&str, &str)] = &[
+    ("324", "https://zksync2-mainnet-explorer.zksync.io/contract_verification"),
+    ("300", "https://explorer.sepolia.era.zksync.dev/contract_verification"),
+    ("2741", "https://api-explorer-verify.mainnet.abs.xyz/contract_verification"),
+    ("11124", "https://api-explorer-verify.testnet.abs.xyz/contract_verification"),
+    ("50104", "https://verification-explorer.sophon.xyz/contract_verification"),
+    ("531050104", "https://api-explorer-verify.testnet.sophon.xyz/contract_verification"),
+];
+
+// Chain names used by Foundry that differ from the names of the registry.
+const ALIASES: &[(&str, &str)] = &[("mainnet", "ethereum")];
+
+/// The built-in registry of chains, giving the id, the name and the explorer of each chain.
+pub struct ChainRegistry;
+
+impl ChainRegistry {
+    /// Returns the chain id of a chain, as named in `foundry.toml`, if it is in the registry.
+    ///
+    /// The name must match a registry name exactly, ignoring the case and with `_` and `-` standing for spaces, e.g.
+    /// `arbitrum_sepolia` -> "Arbitrum Sepolia". `mainnet` is an alias for Ethereum.
+    pub fn chain_id(chain_name: &str) -> Option<u64> {
+        let query = chain_name.to_lowercase().replace(['_', '-'], " ");
+        let query = ALIASES.iter().find(|(alias, _)| *alias == query).map_or(query.as_str(), |(_, name)| name);
+
+        Self::chains().find(|(_, data)| data.name.to_lowercase() == query).map(|(chain_id, _)| chain_id)
+    }
+
+    /// Returns the registry data of a chain id, if any.
+    pub fn get(chain_id: u64) -> Option<&'static ChainData> {
+        Self::chains().find(|(id, _)| *id == chain_id).map(|(_, data)| data)
+    }
+
+    /// Tells whether a chain is a zkEVM chain, such as zkSync, Abstract and Sophon.
+    pub fn is_zkevm(chain_id: u64) -> bool {
+        Self::zk_verifier_url(chain_id).is_some()
+    }
+
+    /// Returns the endpoint of the zkSync verifier of a zkEVM chain.
+    pub fn zk_verifier_url(chain_id: u64) -> Option<&'static str> {
+        ZKEVM_CHAINS.iter().find(|(id, _)| id.parse() == Ok(chain_id)).map(|(_, url)| *url)
+    }
+
+    /// Returns every chain of the registry along with its id, mainnets first.
+    pub fn chains() -> impl Iterator<Item = (u64, &'static ChainData)> {
+        CHAINS.iter().filter_map(|(chain_id, data)| chain_id.parse().ok().map(|chain_id| (chain_id, data)))
+    }
 
-/// Returns the explorer URL based on a partial or complete chain name.
-/// The search is performed in a case-insensitive manner by converting both the input
-/// and each chain's name to lowercase. If a match is found (i.e. the chain's name contains
-/// the provided query), the corresponding explorer URL is returned. Otherwise, it returns "<N/A>".
-pub fn get_explorer_url_by_name(chain_name: &str) -> String {
-    let query = chain_name.to_lowercase();
-    CHAINS
-        .iter()
-        .find(|(_, data)| data.name.to_lowercase().contains(&query))
-        .map(|(_, data)| data.explorer_url.to_string())
-        .unwrap_or_else(|| "<N/A>".to_string())
+    /// Returns the explorer URL based on a partial or complete chain name.
+    /// The search is performed in a case-insensitive manner by converting both the input
+    /// and each chain's name to lowercase. If a match is found (i.e. the chain's name contains
+    /// the provided query), the corresponding explorer URL is returned. Otherwise, it returns "<N/A>".
+    pub fn explorer_url_by_name(chain_name: &str) -> String {
+        let query = chain_name.to_lowercase();
+        Self::chains()
+            .find(|(_, data)| data.name.to_lowercase().contains(&query))
+            .map(|(_, data)| data.explorer_url.to_string())
+            .unwrap_or_else(|| "<N/A>".to_string())
+    }
 }
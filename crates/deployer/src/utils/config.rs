@@ -0,0 +1,55 @@
+use super::{
+    broadcast::SdkSettings, error::Error, gas::GasSettings, naming::ContractSettings, package::Package,
+    verify::VerifySettings,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path};
+
+// The name of the optional deployer configuration file, looked up at the root of the Foundry project.
+pub const CONFIG_FILE: &str = "deployer.toml";
+
+/// Deployer settings that are specific to a project and don't belong in `foundry.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// How the contracts of each project appear in generated files, e.g. `[contracts.lockup]` with
+    /// `list = ["SablierLockup"]`.
+    pub contracts: HashMap<String, ContractSettings>,
+    /// Named groups of chains, e.g. `testnets = ["sepolia", "*_sepolia"]`. Entries can be chain names or globs.
+    pub groups: HashMap<String, Vec<String>>,
+    /// Gas settings per chain, e.g. `[gas.chiliz]` with `with_gas_price = "10000gwei"`.
+    pub gas: HashMap<String, GasSettings>,
+    /// The project of each script, e.g. `"DeployProtocol.s.sol" = "lockup"`, overriding the `package.json` name.
+    pub projects: HashMap<String, String>,
+    /// Where broadcast files are copied to in the SDK, see `[sdk]`.
+    pub sdk: SdkSettings,
+    /// How contracts are verified on the explorers, see `[verify]`.
+    pub verify: VerifySettings,
+}
+
+impl Config {
+    /// Loads the configuration from `deployer.toml`. A missing file yields the default configuration.
+    pub fn load() -> Result<Self, Error> {
+        let path = Path::new(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(path).map_err(|source| Error::Io { path: path.to_path_buf(), source })?;
+        toml::from_str(&content).map_err(|e| Error::Parse { path: path.to_path_buf(), message: e.to_string() })
+    }
+
+    /// Returns the contract settings of the project a script belongs to. The project is only looked up when some
+    /// settings are configured.
+    pub fn contract_settings(
+        &self,
+        script_name: &str,
+    ) -> Result<ContractSettings, Error> {
+        if self.contracts.is_empty() {
+            return Ok(ContractSettings::default());
+        }
+
+        let project = Package::load()?.project(script_name, &self.projects)?;
+        Ok(self.contracts.get(&project).cloned().unwrap_or_default())
+    }
+}
@@ -1,21 +1,71 @@
 // Default Sablier deployer address as a string.
 pub const DEFAULT_DEPLOYER: &str = "0xb1bEF51ebCA01EB12001a639bDBbFF6eEcA12B9F";
 
+// The chain to deploy to when no chain is provided.
+pub const DEFAULT_CHAIN: &str = "sepolia";
+
+// The Foundry profile used for deployments and verifications when none is provided.
+pub const DEFAULT_PROFILE: &str = "optimized";
+
 // A help message for the CLI tool that is displayed when the "--help" flag is passed.
 pub const HELP_MESSAGE: &str = r#"
 Usage:
-  deployer <chain_name> [OPTIONS]
+  deployer <chain_name | group | glob>... [OPTIONS] [-- <FORGE_ARGS>...]
+  deployer copy [chain_name | group | glob]... --script <SCRIPT> [--run <RUN>]
+  deployer report [chain_name | group | glob]... --script <SCRIPT> [--format markdown]
+  deployer manifest [chain_name | group | glob]... --script <SCRIPT> [--output <PATH>]
+  deployer verify [chain_name | group | glob]... --script <SCRIPT> [--run <RUN>]
+  deployer verify --chain <CHAIN> --address <ADDRESS> --verify-contract <NAME> [--args <ARGS>]
+  deployer verify --batch <FILE>
+
+Commands:
+  copy               Copy the broadcasts of the script on each chain to the "sdk" directory
+  manifest           Merge the contracts deployed by the script into a JSON manifest, "deployments.json" by default
+  report             Print the contracts deployed by the script on each chain with a broadcast, with explorer links
+  verify             Verify the contracts deployed by the script, or contracts deployed without a broadcast, given with
+                     flags or in a CSV or JSON batch file
 
 Flags:
+  --account          The "cast wallet" keystore account signing the transactions
+  --address          The address of the contract to verify with "verify"
   --all              Deploy on all chains declared in the "foundry.toml" file
-  --cp-bf            Copy the broadcasts generated by foundry to the "sdk" directory
+  --args             Comma-separated constructor arguments of the contract to verify, as addresses or integers
+  --batch            A CSV or JSON file listing the contracts to verify with "verify"
   --broadcast        Whether the deployment should be broadcasted or not
-  --gas-price        Specify the gas price for the deployment in wei
+  --chain            The chain of the contract to verify with "verify"
+  --contract         The contract to run within the script file, e.g. "DeployFlow"
+  --cp-bf            Copy the broadcasts generated by foundry to the "sdk" directory
+  --emit-script      With "--show", write the commands to an executable bash script, along with the copy and
+                     verification steps, e.g. "--emit-script run.sh"; the contracts are verified with "deployer verify"
+  --exclude          Comma-separated chains, groups or globs to leave out, e.g. "--exclude chiliz,*_sepolia"
+  --export-format    Copy broadcasts "full" as written by forge, or "slim" without bytecode, calldata and logs
+  --force            Overwrite broadcast files that already exist in the "sdk" directory with a different content
+  --fork-check       Run the deployment on local anvil forks first, and only deploy if it succeeds on every fork
+  --format           The format of "report", defaults to "markdown"
+  --gas-estimate-multiplier
+                     The percentage by which gas estimates are multiplied, e.g. "130" or "linea=150"
+  --gas-price        Specify the gas price for the deployment, e.g. "2gwei" or "chiliz=10000gwei"
   --help             Print a help message
-  --print-deployment Generate a TypeScript "deployments.ts" file with contract addresses and block numbers
+  --interactive      Let forge prompt for the private key signing the transactions
+  --output           The file written by "manifest" or "--print-deployment", defaults to "deployments.json" and
+                     "deployments.ts" respectively
+  --print-deployment Update the chains of a TypeScript "deployments.ts" file with contract addresses and block numbers
+  --priority-gas-price
+                     The priority fee of the broadcasted transactions, e.g. "1gwei" or "chiliz=1gwei"
+  --private-key-env  The environment variable holding the private key signing the transactions, e.g. "PRIVATE_KEY"
+  --profile          The Foundry profile to use, defaults to "optimized"
+  --run              The run whose broadcasts are verified and exported: "latest", "dry-run", "run-<timestamp>.json" or
+                     a path where "{chain}" and "{chain_id}" are replaced; defaults to the run of the deployment
   --script           The script to run for deployment
+  --script-dir       The directory containing the script, defaults to "script"
+  --sdk-dir          The root of the "sdk" repository, defaults to "../sdk"
+  --sender           The address of the deployer, defaults to the Sablier deployer
   --show             Instead of executing the deployments, simply prints the CLI commands that would be run
+  --sig              The signature of the script function to call, e.g. "run(address)"
+  --sig-arg          An argument of the script function, ABI-encoded with the signature; repeat for each argument
   --verify           Verify deployment on the respective chain explorer
+  --verify-contract  The contract to verify with "verify", e.g. "SablierLockup", apart from the "--contract" of scripts
+  --with-gas-price   The gas price of the broadcasted transactions, e.g. "chiliz=10000gwei"
 
 Example:
   deployer sepolia --script DeployFlow.s.sol
@@ -25,11 +75,52 @@ Output:
   Running the deployment command:
     FOUNDRY_PROFILE=optimized forge script script/DeployFlow.s.sol --rpc-url sepolia
 
+Forge arguments:
+  Arguments after "--" are passed as-is to "forge script", e.g. "-- --slow --skip-simulation".
+
+Gas settings:
+  Gas flags accept a value for every chain or "<chain>=<value>" for a single chain, and can be repeated. Prices
+  accept a "wei", "gwei" or "ether" suffix and are read as wei without one. Defaults per chain can be declared
+  in "deployer.toml":
+    [gas.chiliz]
+    with_gas_price = "10000gwei"
+    priority_gas_price = "1gwei"
+
+Signers:
+  Only one of "--account", "--private-key-env" and "--interactive" can be provided. The private key is never printed,
+  and before broadcasting the signer address must match "--sender". Forge reads a private key from a temporary
+  keystore, so that it isn't passed in the process arguments. "--emit-script" rejects "--private-key-env", as the
+  script could only pass the key in the process arguments.
+
+zkEVM chains:
+  zkSync, Abstract and Sophon are deployed and verified with "--zksync", building into the "zkout" directory. Unless
+  they have an "[etherscan]" entry, they are verified with their zkSync verifier, which needs no API key. The fork
+  check skips them, and their addresses are left out when warning about contracts deployed at different addresses.
+
+Chain selection:
+  Chains can be given by name, by a group declared in "deployer.toml" or by a glob such as "*_sepolia":
+    [groups]
+    testnets = ["sepolia", "*_sepolia"]
+  Only chains declared in the "foundry.toml" file are selected. Unknown chain names are rejected.
+  When no chain is provided at all, the deployment targets "sepolia".
+
 Assumptions:
   - The script is run within a Foundry project, with a "script" directory at the project root.
   - Chain names match those declared in the "foundry.toml" file.
-  - If "--cp-bf" is used, the "sdk" directory is expected to exist at "../sdk".
-  - If "--verify" is used, Etherscan API keys must be set in the ".env" file: <chain_name_from_foundry_toml>_API_KEY
+  - Chain ids are taken from the built-in chain registry, the "chain" of the "[etherscan]" entry, or else the RPC
+    URL through "cast chain-id". A broadcast written for another chain id stops the deployment.
+  - If "--cp-bf" is used, broadcasts are copied to "<sdk-dir>/deployments/<project>/v<version>/broadcasts/<chain>.json".
+    The layout can be changed in "deployer.toml":
+      [sdk]
+      dir = "../sdk"
+      path = "deployments/{project}/v{version}/broadcasts/{chain}.json"
+      format = "slim"
+    The project is derived from the "name" of "package.json", e.g. "@sablier/flow" -> "flow", unless the script is
+    mapped in "deployer.toml":
+      [projects]
+      "DeployProtocol.s.sol" = "lockup"
+  - If "--verify" is used, the chain's "[etherscan]" entry in "foundry.toml" is used when there is one. Otherwise,
+    Etherscan API keys must be set in the ".env" file: <chain_name_from_foundry_toml>_API_KEY
     Example:
       MAINNET_API_KEY for "mainnet"
       AVALANCHE_API_KEY for "avalanche"
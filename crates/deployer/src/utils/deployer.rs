@@ -0,0 +1,657 @@
+use super::{
+    broadcast::{BroadcastFile, BroadcastRun},
+    chain_data::ChainRegistry,
+    config::Config,
+    constants,
+    error::Error,
+    export::{ExportFormat, Exporter, SlimBroadcast},
+    forge::ScriptOptions,
+    foundry::FoundryConfig,
+    gas::GasOverrides,
+    shell::{self, ShellScript},
+    signer::Signer,
+    toolchain::{self, Forge, ToolchainRunner},
+    verify::Verifier,
+};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+// Where the broadcasts of the fork checks are written, apart from the real ones. Foundry projects ignore `cache`.
+const FORK_BROADCAST_DIR: &str = "cache/fork-check";
+
+// Anvil impersonates every sender of the forks, so the fork checks don't need the signer.
+const FORK_SIGNER_ARGS: [&str; 1] = ["--unlocked"];
+
+/// What a deployment does besides running the script.
+#[derive(Debug)]
+pub struct DeployOptions {
+    /// Whether the transactions are broadcasted, rather than only simulated.
+    pub broadcast: bool,
+    /// Whether the broadcast files are copied to the SDK.
+    pub copy_broadcast: bool,
+    /// The bash script the shown commands are written to, if any, along with the steps that follow the deployment.
+    pub emit_script: Option<PathBuf>,
+    /// Whether broadcast files that already exist in the SDK with a different content are overwritten.
+    pub force: bool,
+    /// Gas settings provided on the command line.
+    pub gas: GasOverrides,
+    /// The TypeScript deployments file updated with the deployed chains, if any.
+    pub print_deployment: Option<PathBuf>,
+    /// The run whose broadcasts are verified and exported, rather than the run of the deployment.
+    pub run: Option<BroadcastRun>,
+    /// The address of the deployer.
+    pub sender: String,
+    /// Whether the commands are only printed instead of being run.
+    pub show_cli: bool,
+    /// How forge signs the transactions.
+    pub signer: Signer,
+    /// Whether the deployment is first run on local forks of the chains, and only broadcasted if it succeeds on all of
+    /// them.
+    pub fork_check: bool,
+    /// Whether the deployed contracts are verified.
+    pub verify: bool,
+    /// How long to wait before verifying, for the explorers to index the deployments.
+    pub verify_delay: Duration,
+}
+
+impl Default for DeployOptions {
+    fn default() -> Self {
+        DeployOptions {
+            broadcast: false,
+            copy_broadcast: false,
+            emit_script: None,
+            force: false,
+            fork_check: false,
+            gas: GasOverrides::default(),
+            print_deployment: None,
+            run: None,
+            sender: constants::DEFAULT_DEPLOYER.to_string(),
+            show_cli: false,
+            signer: Signer::default(),
+            verify: false,
+            verify_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Runs a deployment script on multiple chains with `forge script`, then exports and verifies the deployments.
+pub struct Deployer {
+    config: Config,
+    foundry: FoundryConfig,
+    script: ScriptOptions,
+    options: DeployOptions,
+    runner: Box<dyn ToolchainRunner>,
+}
+
+impl Deployer {
+    /// Creates a deployer for a script of the Foundry project in the current directory, run with the installed
+    /// Foundry.
+    pub fn new(
+        config: Config,
+        foundry: FoundryConfig,
+        script: ScriptOptions,
+        options: DeployOptions,
+    ) -> Self {
+        Deployer { config, foundry, script, options, runner: Box::new(Forge) }
+    }
+
+    /// Runs the Foundry commands with another toolchain, e.g. a fake one in tests.
+    pub fn with_runner(
+        mut self,
+        runner: impl ToolchainRunner + 'static,
+    ) -> Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    /// Deploys on the given chains, which must be declared in `foundry.toml`.
+    ///
+    /// Invalid settings are reported before anything runs. When forge fails on a chain, or its broadcast file can't be
+    /// copied, the error is reported and the remaining chains are still deployed; the failed chains are returned as an
+    /// error at the end. Likewise, the contracts that couldn't be verified are returned once the deployment has
+    /// finished.
+    pub fn deploy(
+        &self,
+        chains: &[String],
+    ) -> Result<(), Error> {
+        let options = &self.options;
+        let script_args = self.script.args()?;
+
+        // The script holds the commands that are shown, rather than the outcome of running them
+        if options.emit_script.is_some() && !options.show_cli {
+            return Err(Error::InvalidArgument("--emit-script can only be used with --show".to_string()));
+        }
+
+        // Forge only reads a private key from the arguments or from a keystore, which the script can't create without
+        // passing the key in the arguments of another command
+        if let (Some(_), Signer::PrivateKeyEnv(var)) = (&options.emit_script, &options.signer) {
+            return Err(Error::PrivateKeyInScript { var: var.clone() });
+        }
+
+        // Gas overrides must target chains that are configured in the TOML file
+        let configured_chains = self.foundry.chains();
+        if let Some(chain) = options.gas.per_chain.keys().find(|chain| !configured_chains.contains(chain)) {
+            return Err(Error::InvalidArgument(format!(
+                "Gas settings were provided for chain {}, which is not configured in the TOML file",
+                chain
+            )));
+        }
+
+        // Make sure the RPC URLs, the chain ids and the gas settings of the selected chains are valid before running
+        // anything. The arguments specific to each chain are its gas settings and, on zkEVM chains, the zkSync mode.
+        let mut chain_ids = HashMap::new();
+        let mut chain_args = HashMap::new();
+        for chain in chains {
+            self.foundry.rpc_url(chain)?;
+            let chain_id = self.foundry.chain_id(chain, self.runner.as_ref())?;
+            let mut args = options.gas.resolve(chain, self.config.gas.get(chain)).args()?;
+            if ChainRegistry::is_zkevm(chain_id) {
+                args.push("--zksync".to_string());
+            }
+            chain_ids.insert(chain.clone(), chain_id);
+            chain_args.insert(chain.clone(), args);
+        }
+
+        // Make sure the transactions are signed by the sender before broadcasting them
+        if options.broadcast && !options.show_cli {
+            options.signer.check_sender(&options.sender, self.runner.as_ref())?;
+        }
+
+        // The temporary keystore of a private key is kept until the deployment ends
+        let signer_args = options.signer.args(options.show_cli)?;
+
+        // The shown commands are also written to a script, for an operator to review and run by hand
+        let mut shell_script = match options.emit_script {
+            Some(_) => {
+                let dir = env::current_dir().map_err(|source| Error::Io { path: PathBuf::from("."), source })?;
+                let description =
+                    format!("Deploys {} on {}, as shown by the deployer", self.script.name, chains.join(", "));
+                Some(ShellScript::new(&description, &dir))
+            }
+            None => None,
+        };
+
+        // Compile the contracts once, rather than failing on every chain when they don't compile
+        self.build(chains, &chain_ids, &mut shell_script)?;
+
+        // Chains on which a step failed, the errors being reported as they happen so that the other chains still run
+        let mut failed_chains = Vec::new();
+        let mut undeployed_chains = Vec::new();
+
+        // Chains where the contracts already exist are skipped, as redeploying them would revert
+        let mut chains = chains.to_vec();
+        if options.broadcast && !options.show_cli {
+            chains = self.skip_deployed_chains(&chains, &chain_ids, &script_args, &chain_args, &mut failed_chains)?;
+        }
+
+        // Nothing is broadcasted unless the deployment succeeds on a fork of every chain
+        if options.fork_check && !chains.is_empty() {
+            self.fork_check(&chains, &chain_ids, &script_args, &chain_args)?;
+        }
+
+        // The broadcasts of the deployment are verified and exported, unless another run is selected
+        let run = options.run.clone().unwrap_or_else(|| BroadcastRun::latest(options.broadcast));
+        let exporter = Exporter::new(&self.config, &self.script.name, &chain_ids).with_run(run.clone());
+
+        // Output the list of unique chains
+        if !chains.is_empty() {
+            println!("\nDeploying to the chains: {}\n", chains.join(", "));
+        }
+
+        // Iterate over the provided chains and run the deployment command
+        for chain in &chains {
+            let command_args = self.script_command(
+                &script_args,
+                chain,
+                chain,
+                options.broadcast,
+                &chain_args[chain],
+                &signer_args.args,
+            );
+
+            // The environment of the forge processes, set for each of them rather than for the deployer
+            let env = self.foundry.chain_env(chain_ids[chain]);
+            let full_command = toolchain::command_line(&env, "script", &command_args);
+
+            if options.show_cli {
+                println!("Command to be executed: {} \n", full_command);
+                if let Some(shell_script) = &mut shell_script {
+                    shell_script.step(&format!("Deploy on {}", chain));
+                    shell_script.command(&full_command);
+                    if options.copy_broadcast {
+                        shell_script.step(&format!("Copy the broadcast of {} to the SDK", chain));
+                        shell_script.command(&self.copy_command(chain, &run));
+                    }
+                }
+                continue;
+            }
+
+            println!("Running the deployment command: {}", full_command);
+
+            // Run the script, noting which broadcasts existed to tell which one it writes
+            let broadcast = BroadcastFile::new(&self.script.name, chain, chain_ids[chain], options.broadcast);
+            let modified_times = broadcast.modified_times();
+            let output = self.runner.run_script(&command_args, &env)?;
+
+            // Process command output, skipping the rest of the chain when the script failed
+            if !output.success {
+                eprintln!("Error: {}\n", Error::Forge { chain: chain.clone(), stderr: output.stderr });
+                undeployed_chains.push(chain.clone());
+                failed_chains.push(chain.clone());
+                continue;
+            }
+            println!("Command output: {}\n", output.stdout);
+
+            // A broadcast written for another chain id means the RPC URL points to another network, so stop here
+            if let Err(e @ Error::ChainIdMismatch { .. }) = broadcast.check_chain_id(&modified_times) {
+                return Err(e);
+            }
+
+            if options.copy_broadcast {
+                if let Err(e) = exporter.copy(&exporter.broadcast(chain), options.force) {
+                    eprintln!("Error: {}\n", e);
+                    failed_chains.push(chain.clone());
+                }
+            }
+        }
+
+        // Only the chains the script ran successfully on have deployments to print or verify
+        let deployed_chains: Vec<String> =
+            chains.iter().filter(|chain| !undeployed_chains.contains(chain)).cloned().collect();
+
+        // Deterministic deployments have the same addresses on every chain, except on zkEVM chains
+        if options.broadcast && !options.show_cli && deployed_chains.len() > 1 {
+            match exporter.address_mismatches(&deployed_chains) {
+                Ok(mismatches) => {
+                    for mismatch in mismatches {
+                        println!("Warning: {}\n", mismatch);
+                    }
+                }
+                Err(e) => eprintln!("Error: the addresses can't be compared across chains: {}\n", e),
+            }
+        }
+
+        // Update the TypeScript deployments, which need the blocks of the broadcasted transactions. An error is
+        // reported once the verification ran.
+        let mut print_result = Ok(());
+        if let Some(path) = &options.print_deployment {
+            if options.show_cli || run == BroadcastRun::DryRun {
+                println!("The TypeScript deployments are only updated for broadcasted deployments\n");
+            } else if !deployed_chains.is_empty() {
+                print_result = exporter.typescript(path, &deployed_chains);
+            }
+        }
+
+        // If the verify flag is set, run the verification process
+        let mut failed_verifications = Vec::new();
+        if options.verify {
+            if !options.show_cli && !options.verify_delay.is_zero() {
+                println!(
+                    "Waiting for {} seconds to allow explorer to process deployments... \n",
+                    options.verify_delay.as_secs()
+                );
+                thread::sleep(options.verify_delay);
+            }
+            if options.show_cli {
+                // The broadcasts to verify only exist once the shown commands ran, so the deployer verifies them then
+                if let Some(shell_script) = &mut shell_script {
+                    shell_script.step("Wait for the explorers to index the deployments");
+                    if !options.verify_delay.is_zero() {
+                        shell_script.command(&format!("sleep {}", options.verify_delay.as_secs()));
+                    }
+                }
+                for chain in &deployed_chains {
+                    let command = self.verify_command(chain, &run);
+                    println!("Verification command to be executed: {} \n", command);
+                    if let Some(shell_script) = &mut shell_script {
+                        shell_script.step(&format!("Verify the contracts on {}", chain));
+                        shell_script.command(&command);
+                    }
+                }
+            } else {
+                // The failures are reported as they happen, and returned once the deployment has finished
+                let verifier = Verifier::new(self.runner.as_ref(), &self.foundry, &self.config.verify, false);
+                failed_verifications = verifier.verify_all(&self.script.name, &run, &deployed_chains, &chain_ids);
+            }
+        }
+
+        if let (Some(shell_script), Some(path)) = (&shell_script, &options.emit_script) {
+            shell_script.write(path)?;
+            println!("The commands were written to {}, review it before running it\n", path.display());
+        }
+
+        if !failed_chains.is_empty() {
+            return Err(Error::FailedChains(failed_chains));
+        }
+        if !failed_verifications.is_empty() {
+            return Err(Error::FailedVerifications(failed_verifications));
+        }
+
+        print_result
+    }
+
+    // Returns the deployer command verifying the contracts of a run of the script on a chain, as shown and written to
+    // the emitted script. The profile of the deployment is passed along, as the command runs in another process.
+    fn verify_command(
+        &self,
+        chain: &str,
+        run: &BroadcastRun,
+    ) -> String {
+        let words: Vec<String> = ["deployer", "verify", chain, "--script", &self.script.name]
+            .into_iter()
+            .map(String::from)
+            .chain(["--run".to_string(), run.to_string()])
+            .chain(["--profile".to_string(), self.foundry.profile.clone()])
+            .collect();
+        shell::join(&words)
+    }
+
+    // Returns the deployer command copying the broadcast of a run of the script on a chain to the SDK, as written to
+    // the emitted script. The settings of the deployment are passed along, as the command runs in another process.
+    fn copy_command(
+        &self,
+        chain: &str,
+        run: &BroadcastRun,
+    ) -> String {
+        let format = match self.config.sdk.format {
+            ExportFormat::Full => "full",
+            ExportFormat::Slim => "slim",
+        };
+        let mut words: Vec<String> =
+            ["deployer", "copy", chain, "--script", &self.script.name].into_iter().map(String::from).collect();
+        words.extend(["--run".to_string(), run.to_string()]);
+        words.extend(["--sdk-dir".to_string(), self.config.sdk.dir.clone()]);
+        words.extend(["--export-format".to_string(), format.to_string()]);
+        if self.options.force {
+            words.push("--force".to_string());
+        }
+        shell::join(&words)
+    }
+
+    // Compiles the contracts with `forge build`, once for the EVM chains and once in zkSync mode for the zkEVM chains.
+    fn build(
+        &self,
+        chains: &[String],
+        chain_ids: &HashMap<String, u64>,
+        shell_script: &mut Option<ShellScript>,
+    ) -> Result<(), Error> {
+        let mut builds = Vec::new();
+        if chains.iter().any(|chain| !ChainRegistry::is_zkevm(chain_ids[chain])) {
+            builds.push((self.foundry.env(), Vec::new()));
+        }
+        if let Some(chain) = chains.iter().find(|chain| ChainRegistry::is_zkevm(chain_ids[*chain])) {
+            builds.push((self.foundry.chain_env(chain_ids[chain]), vec!["--zksync".to_string()]));
+        }
+
+        for (env, args) in builds {
+            let full_command = toolchain::command_line(&env, "build", &args);
+            if self.options.show_cli {
+                println!("Build command to be executed: {} \n", full_command);
+                if let Some(shell_script) = shell_script {
+                    shell_script.step("Build the contracts");
+                    shell_script.command(&full_command);
+                }
+                continue;
+            }
+
+            println!("Building the contracts: {}\n", full_command);
+            let output = self.runner.build(&args, &env)?;
+            if !output.success {
+                return Err(Error::BuildFailed { stderr: output.stderr });
+            }
+        }
+        Ok(())
+    }
+
+    // Simulates the deployment on each chain and looks for code at the addresses of the contracts it would create.
+    // Returns the chains to deploy to, leaving out those where every contract exists. The chains where only some
+    // contracts exist, or where the simulation failed, are reported and added to the failed chains.
+    fn skip_deployed_chains(
+        &self,
+        chains: &[String],
+        chain_ids: &HashMap<String, u64>,
+        script_args: &[String],
+        chain_args: &HashMap<String, Vec<String>>,
+        failed_chains: &mut Vec<String>,
+    ) -> Result<Vec<String>, Error> {
+        let mut pending = Vec::new();
+        for chain in chains {
+            match self.deployed_contracts(chain, chain_ids[chain], script_args, &chain_args[chain]) {
+                Ok((deployed, _)) if deployed.is_empty() => pending.push(chain.clone()),
+                Ok((deployed, remaining)) if remaining.is_empty() => {
+                    println!("Already deployed on {}, skipping it: {}\n", chain, deployed.join(", "));
+                }
+                Ok((deployed, remaining)) => {
+                    eprintln!("Error: {}\n", Error::PartialDeployment { chain: chain.clone(), deployed, remaining });
+                    failed_chains.push(chain.clone());
+                }
+                Err(e @ Error::ChainIdMismatch { .. }) => return Err(e),
+                Err(e) => {
+                    eprintln!("Error: {}\n", e);
+                    failed_chains.push(chain.clone());
+                }
+            }
+        }
+
+        Ok(pending)
+    }
+
+    // Runs the script without broadcasting on a chain, for the given purpose, and returns the broadcast of the dry run.
+    // A run without transactions writes no broadcast, in which case the one of a previous run is left as is and `None`
+    // is returned.
+    fn simulate(
+        &self,
+        chain: &str,
+        chain_id: u64,
+        script_args: &[String],
+        chain_args: &[String],
+        purpose: &str,
+    ) -> Result<Option<BroadcastFile>, Error> {
+        // The simulation needs no signer, which could prompt for a password
+        let env = self.foundry.chain_env(chain_id);
+        let args = self.script_command(script_args, chain, chain, false, chain_args, &[]);
+        println!("Simulating the deployment to {}: {}", purpose, toolchain::command_line(&env, "script", &args));
+
+        let broadcast = BroadcastFile::new(&self.script.name, chain, chain_id, false);
+        let modified_times = broadcast.modified_times();
+        let output = self.runner.run_script(&args, &env)?;
+        if !output.success {
+            return Err(Error::Forge { chain: chain.to_string(), stderr: output.stderr });
+        }
+        Ok(broadcast.check_chain_id(&modified_times)?.then_some(broadcast))
+    }
+
+    // Runs the script without broadcasting on a chain, then returns the contracts of the dry run that already have code
+    // on the chain, and the ones that don't, as "<name> at <address>".
+    fn deployed_contracts(
+        &self,
+        chain: &str,
+        chain_id: u64,
+        script_args: &[String],
+        chain_args: &[String],
+    ) -> Result<(Vec<String>, Vec<String>), Error> {
+        let Some(broadcast) =
+            self.simulate(chain, chain_id, script_args, chain_args, "check for existing contracts")?
+        else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+
+        let rpc_url = self.foundry.rpc_url(chain)?;
+        let (mut deployed, mut remaining) = (Vec::new(), Vec::new());
+        for contract in SlimBroadcast::from_json(&broadcast.read()?.1).contracts {
+            let name = contract.contract_name.as_deref().unwrap_or("an unnamed contract");
+            let description = format!("{} at {}", name, contract.contract_address);
+            if !self.has_code(chain, &contract.contract_address, &rpc_url)? {
+                remaining.push(description);
+            } else {
+                deployed.push(description);
+            }
+        }
+
+        Ok((deployed, remaining))
+    }
+
+    // Builds the `forge script` arguments of a run on a chain, against the given RPC URL.
+    fn script_command(
+        &self,
+        script_args: &[String],
+        chain: &str,
+        rpc_url: &str,
+        broadcast: bool,
+        chain_args: &[String],
+        signer_args: &[String],
+    ) -> Vec<String> {
+        let mut command_args = script_args.to_vec();
+        command_args.push("--rpc-url".to_string());
+        command_args.push(rpc_url.to_string());
+
+        if broadcast {
+            command_args.push("--broadcast".to_string());
+        }
+
+        command_args.extend(chain_args.iter().cloned());
+
+        // Push the sender flag.
+        command_args.push("--sender".to_string());
+
+        // Push the sender address.
+        command_args.push(self.options.sender.to_string());
+
+        // Push the signer flags.
+        command_args.extend(signer_args.iter().cloned());
+
+        // Add the legacy flag for the "chiliz", "form, and "linea" chains, due to the lack of EIP-3855 support.
+        if chain.eq("chiliz") || chain.eq("form") || chain.eq("linea") {
+            command_args.push("--legacy".to_string());
+        }
+
+        // Push the pass-through arguments last, so they can override the ones above.
+        command_args.extend(self.script.extra_args.iter().cloned());
+
+        command_args
+    }
+
+    // Runs the script with `--broadcast` on a local fork of each chain, then checks that every contract it created has
+    // code at its address on the fork. The errors are reported for each chain, and the check fails if any chain failed.
+    fn fork_check(
+        &self,
+        chains: &[String],
+        chain_ids: &HashMap<String, u64>,
+        script_args: &[String],
+        chain_args: &HashMap<String, Vec<String>>,
+    ) -> Result<(), Error> {
+        // The fork broadcasts are kept apart from the real ones
+        let mut env = self.foundry.env();
+        env.push(("FOUNDRY_BROADCAST".to_string(), FORK_BROADCAST_DIR.to_string()));
+
+        let mut failed_chains = Vec::new();
+        for chain in chains {
+            if ChainRegistry::is_zkevm(chain_ids[chain]) {
+                println!("Skipping the fork check on {}, as anvil can't fork zkEVM chains\n", chain);
+                continue;
+            }
+
+            if self.options.show_cli {
+                let args = self.script_command(
+                    script_args,
+                    chain,
+                    "$FORK_URL",
+                    true,
+                    &chain_args[chain],
+                    &FORK_SIGNER_ARGS.map(String::from),
+                );
+                println!(
+                    "Fork check command to be executed against anvil --fork-url <{} RPC URL> --auto-impersonate: {} \n",
+                    chain,
+                    toolchain::command_line(&env, "script", &args)
+                );
+                continue;
+            }
+
+            match self.fork_check_chain(chain, chain_ids[chain], script_args, &chain_args[chain], &env) {
+                Ok(count) => println!("The fork check passed on {}, {} contracts were deployed\n", chain, count),
+                Err(e) => {
+                    eprintln!("Error: {}\n", Error::ForkCheck { chain: chain.clone(), message: e.to_string() });
+                    failed_chains.push(chain.clone());
+                }
+            }
+        }
+
+        if !failed_chains.is_empty() {
+            return Err(Error::ForkCheckFailed(failed_chains));
+        }
+        Ok(())
+    }
+
+    // Runs the fork check of a chain, returning the number of contracts deployed on the fork.
+    fn fork_check_chain(
+        &self,
+        chain: &str,
+        chain_id: u64,
+        script_args: &[String],
+        chain_args: &[String],
+        env: &[(String, String)],
+    ) -> Result<usize, Error> {
+        // A run without transactions writes no broadcast, so the one of a previous check must not be read instead
+        let broadcast =
+            BroadcastFile::new(&self.script.name, chain, chain_id, true).in_dir(Path::new(FORK_BROADCAST_DIR));
+        remove_broadcast(&broadcast)?;
+
+        let fork = self.runner.start_fork(&self.foundry.rpc_url(chain)?)?;
+        let args =
+            self.script_command(script_args, chain, &fork.url, true, chain_args, &FORK_SIGNER_ARGS.map(String::from));
+        println!("Running the fork check command: {}", toolchain::command_line(env, "script", &args));
+
+        let output = self.runner.run_script(&args, env)?;
+        if !output.success {
+            return Err(Error::Forge { chain: chain.to_string(), stderr: output.stderr });
+        }
+
+        let (_, json) = broadcast.read()?;
+        let contracts = SlimBroadcast::from_json(&json).contracts;
+        for contract in &contracts {
+            if !self.has_code(chain, &contract.contract_address, &fork.url)? {
+                return Err(Error::NoCode {
+                    chain: chain.to_string(),
+                    address: contract.contract_address.clone(),
+                    contract: contract.contract_name.clone().unwrap_or_else(|| "an unnamed contract".to_string()),
+                });
+            }
+        }
+
+        Ok(contracts.len())
+    }
+
+    // Tells whether code is deployed at an address of a chain, or of its fork, behind an RPC URL.
+    fn has_code(
+        &self,
+        chain: &str,
+        address: &str,
+        rpc_url: &str,
+    ) -> Result<bool, Error> {
+        let output = self.runner.code_at(address, rpc_url)?;
+        if !output.success {
+            return Err(Error::Rpc {
+                chain: chain.to_string(),
+                message: format!("cast code {} failed with: {}", address, output.stderr.trim()),
+            });
+        }
+        Ok(!output.stdout.trim().trim_start_matches("0x").is_empty())
+    }
+}
+
+// Removes a broadcast file before the run that writes it, if it exists.
+fn remove_broadcast(broadcast: &BroadcastFile) -> Result<(), Error> {
+    match fs::remove_file(broadcast.path()) {
+        Err(source) if source.kind() != ErrorKind::NotFound => {
+            Err(Error::Io { path: broadcast.path().to_path_buf(), source })
+        }
+        _ => Ok(()),
+    }
+}
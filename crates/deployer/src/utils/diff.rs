@@ -0,0 +1,112 @@
+// The maximum number of changed lines printed, so that a diff of two large broadcasts stays readable.
+const MAX_DIFF_LINES: usize = 200;
+
+// Above this many lines on each side, the changed region is printed as a whole instead of being aligned line by line.
+const MAX_ALIGNED_LINES: usize = 1_000;
+
+/// Returns a line-based diff between two texts, with removed lines prefixed by `-` and added lines by `+`.
+///
+/// The lines shared at the start and the end of both texts are skipped, and the region in between is aligned on its
+/// longest common subsequence when it is small enough.
+pub fn diff_lines(
+    old: &str,
+    new: &str,
+) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let old_changed = &old[prefix..old.len() - suffix];
+    let new_changed = &new[prefix..new.len() - suffix];
+
+    let mut lines = vec![format!("@@ line {} @@", prefix + 1)];
+    if old_changed.len() <= MAX_ALIGNED_LINES && new_changed.len() <= MAX_ALIGNED_LINES {
+        lines.extend(aligned(old_changed, new_changed));
+    } else {
+        lines.extend(old_changed.iter().map(|line| format!("-{}", line)));
+        lines.extend(new_changed.iter().map(|line| format!("+{}", line)));
+    }
+
+    let hidden = lines.len().saturating_sub(MAX_DIFF_LINES + 1);
+    lines.truncate(MAX_DIFF_LINES + 1);
+    if hidden > 0 {
+        lines.push(format!("... {} more lines", hidden));
+    }
+
+    lines.join("\n")
+}
+
+// Aligns two sequences of lines on their longest common subsequence.
+fn aligned(
+    old: &[&str],
+    new: &[&str],
+) -> Vec<String> {
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] =
+                if old[i] == new[j] { lengths[i + 1][j + 1] + 1 } else { lengths[i + 1][j].max(lengths[i][j + 1]) };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!(" {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            lines.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_insertions_and_deletions_between_the_shared_lines() {
+        let old = "{\n  \"chain\": 1,\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3\n}\n";
+        let new = "{\n  \"chain\": 1,\n  \"b\": 2,\n  \"c\": 3,\n  \"d\": 4\n}\n";
+
+        assert_eq!(
+            diff_lines(old, new),
+            "@@ line 3 @@\n-  \"a\": 1,\n   \"b\": 2,\n-  \"c\": 3\n+  \"c\": 3,\n+  \"d\": 4"
+        );
+    }
+
+    #[test]
+    fn lists_only_insertions_or_deletions() {
+        assert_eq!(diff_lines("a\nc\n", "a\nb\nc\n"), "@@ line 2 @@\n+b");
+        assert_eq!(diff_lines("a\nb\nc\n", "a\nc\n"), "@@ line 2 @@\n-b");
+        assert_eq!(diff_lines("", "a\n"), "@@ line 1 @@\n+a");
+    }
+
+    #[test]
+    fn has_no_changed_line_for_identical_texts() {
+        assert_eq!(diff_lines("a\nb\n", "a\nb\n"), "@@ line 3 @@");
+    }
+
+    #[test]
+    fn truncates_long_diffs() {
+        let old: String = (0..300).map(|i| format!("old {}\n", i)).collect();
+        let new: String = (0..300).map(|i| format!("new {}\n", i)).collect();
+
+        let diff = diff_lines(&old, &new);
+        let lines: Vec<&str> = diff.lines().collect();
+
+        assert_eq!(lines.len(), MAX_DIFF_LINES + 2);
+        assert_eq!(lines[1], "-old 0");
+        assert_eq!(lines[MAX_DIFF_LINES + 1], "... 400 more lines");
+    }
+}
@@ -0,0 +1,219 @@
+use std::{fmt, io, path::PathBuf};
+
+/// A chain that is not declared in `foundry.toml`, with the closest declared chains.
+#[derive(Debug)]
+pub struct UnknownChain {
+    pub chain: String,
+    pub suggestions: Vec<String>,
+}
+
+/// The errors of the deployer, carrying enough context to be acted upon.
+#[derive(Debug)]
+pub enum Error {
+    /// A flag was passed without its value.
+    MissingFlagValue { flag: String },
+    /// A flag or a configuration value provided by the user is invalid.
+    InvalidArgument(String),
+    /// A file the deployer relies on doesn't exist.
+    MissingFile { path: PathBuf, hint: String },
+    /// A file couldn't be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// A file couldn't be parsed.
+    Parse { path: PathBuf, message: String },
+    /// Chains that are not declared in `foundry.toml` were provided.
+    UnknownChains(Vec<UnknownChain>),
+    /// No chain is left after applying the selection and the exclusions.
+    EmptySelection,
+    /// An environment variable referenced by the configuration is not set.
+    MissingVariable { name: String, context: String },
+    /// The project a script belongs to couldn't be determined.
+    UnknownProject { script: String },
+    /// The RPC of a chain couldn't be queried.
+    Rpc { chain: String, message: String },
+    /// No broadcast of a run of a script was found on the selected chains.
+    MissingBroadcasts { script: String, run: String },
+    /// A contract created by a broadcast has no name, which the exports need.
+    UnnamedContract { chain: String, address: String },
+    /// A contract created by a broadcast has no receipt, as when the broadcast is a dry run.
+    MissingReceipt { chain: String, contract: String },
+    /// An existing manifest is for another project or version than the one being written.
+    ManifestMismatch { path: PathBuf, existing: String, expected: String },
+    /// A manifest doesn't match the published schema.
+    InvalidManifest { path: PathBuf, message: String },
+    /// A broadcast file was written for another chain than the one it was expected for.
+    ChainIdMismatch { path: PathBuf, chain: String, expected: u64, actual: u64 },
+    /// A broadcast file already exists in the SDK with a different content.
+    DestinationExists { path: PathBuf },
+    /// The signer doesn't match the sender of the deployment.
+    SignerMismatch { signer: String, sender: String },
+    /// The address of a keystore account couldn't be read, its error having been reported by `cast`.
+    Keystore { account: String },
+    /// The emitted script would pass the private key of an environment variable in the process arguments.
+    PrivateKeyInScript { var: String },
+    /// An external program couldn't be started.
+    Spawn { program: String, source: io::Error },
+    /// A Foundry command exited with an error.
+    Forge { chain: String, stderr: String },
+    /// The contracts failed to compile, so nothing was run on the chains.
+    BuildFailed { stderr: String },
+    /// Some chains failed, their errors having been reported along the way.
+    FailedChains(Vec<String>),
+    /// Anvil couldn't start a local fork of a chain.
+    ForkStart { message: String },
+    /// A contract created on the local fork of a chain has no code.
+    NoCode { chain: String, address: String, contract: String },
+    /// The deployment failed on the local fork of a chain.
+    ForkCheck { chain: String, message: String },
+    /// The fork check failed on some chains, so nothing was broadcasted.
+    ForkCheckFailed(Vec<String>),
+    /// Some contracts couldn't be verified, their errors having been reported along the way.
+    FailedVerifications(Vec<String>),
+    /// Only some of the contracts of the deployment exist on a chain.
+    PartialDeployment { chain: String, deployed: Vec<String>, remaining: Vec<String> },
+}
+
+impl fmt::Display for Error {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Error::MissingFlagValue { flag } => write!(f, "The {} flag expects a value, see --help", flag),
+            Error::InvalidArgument(message) => write!(f, "{}", message),
+            Error::MissingFile { path, hint } => write!(f, "{} was not found, {}", path.display(), hint),
+            Error::Io { path, source } => write!(f, "Failed to access {}: {}", path.display(), source),
+            Error::Parse { path, message } => write!(f, "Failed to parse {}: {}", path.display(), message),
+            Error::UnknownChains(chains) => {
+                let messages: Vec<String> = chains
+                    .iter()
+                    .map(|unknown| {
+                        if unknown.suggestions.is_empty() {
+                            format!("Chain {} is not configured in the TOML file", unknown.chain)
+                        } else {
+                            format!(
+                                "Chain {} is not configured in the TOML file, did you mean: {}?",
+                                unknown.chain,
+                                unknown.suggestions.join(", ")
+                            )
+                        }
+                    })
+                    .collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            Error::EmptySelection => {
+                write!(
+                    f,
+                    "The chain selection is empty, no configured chain matches the provided chains and exclusions"
+                )
+            }
+            Error::MissingVariable { name, context } => {
+                write!(f, "{} references the {} variable, which is not set; add it to .env", context, name)
+            }
+            Error::UnknownProject { script } => write!(
+                f,
+                "Unknown project for the script {}, set the \"name\" of package.json or map the script in the \
+                 [projects] section of deployer.toml",
+                script
+            ),
+            Error::Rpc { chain, message } => {
+                write!(f, "For chain {}, the RPC request failed: {}; check its RPC URL", chain, message.trim_end())
+            }
+            Error::MissingBroadcasts { script, run } => write!(
+                f,
+                "No broadcast of {} was found for the {} run on the selected chains, run the deployment with \
+                 --broadcast first",
+                script, run
+            ),
+            Error::UnnamedContract { chain, address } => {
+                write!(f, "For chain {}, the contract at {} has no name in the broadcast", chain, address)
+            }
+            Error::MissingReceipt { chain, contract } => write!(
+                f,
+                "For chain {}, the broadcast has no receipt for {}, make sure the deployment was broadcasted",
+                chain, contract
+            ),
+            Error::ManifestMismatch { path, existing, expected } => write!(
+                f,
+                "The manifest {} is for {}, not {}; pass another path with --output",
+                path.display(),
+                existing,
+                expected
+            ),
+            Error::InvalidManifest { path, message } => {
+                write!(f, "The manifest for {} doesn't match its schema: {}", path.display(), message)
+            }
+            Error::ChainIdMismatch { path, chain, expected, actual } => write!(
+                f,
+                "The broadcast file {} is for the chain id {}, but {} has the chain id {}; check the RPC URL of {} in \
+                 foundry.toml",
+                path.display(),
+                actual,
+                chain,
+                expected,
+                chain
+            ),
+            Error::DestinationExists { path } => write!(
+                f,
+                "The broadcast file {} already exists with a different content, pass --force to overwrite it",
+                path.display()
+            ),
+            Error::SignerMismatch { signer, sender } => write!(
+                f,
+                "The signer address {} doesn't match the sender {}, pass the signer's address with --sender",
+                signer, sender
+            ),
+            Error::Keystore { account } => {
+                write!(f, "Failed to read the address of the account {}, see the error of cast above", account)
+            }
+            Error::PrivateKeyInScript { var } => write!(
+                f,
+                "The emitted script can't sign with the private key of {} without passing it in the process arguments; \
+                 sign with --account or --interactive instead",
+                var
+            ),
+            Error::Spawn { program, source } => {
+                write!(f, "Failed to run {}: {}; make sure Foundry is installed and in your PATH", program, source)
+            }
+            Error::Forge { chain, stderr } => {
+                write!(f, "For chain {}, forge failed with:\n{}", chain, stderr.trim_end())
+            }
+            Error::BuildFailed { stderr } => {
+                write!(f, "The contracts failed to compile, so nothing was run on the chains:\n{}", stderr.trim_end())
+            }
+            Error::FailedChains(chains) => {
+                write!(f, "Failed on the chains: {}, see the errors above", chains.join(", "))
+            }
+            Error::ForkStart { message } => write!(f, "Failed to start a fork with anvil: {}", message.trim_end()),
+            Error::NoCode { chain, address, contract } => {
+                write!(f, "For chain {}, no code was deployed at {} for {}", chain, address, contract)
+            }
+            Error::ForkCheck { chain, message } => {
+                write!(f, "For chain {}, the fork check failed: {}", chain, message.trim_end())
+            }
+            Error::ForkCheckFailed(chains) => write!(
+                f,
+                "The fork check failed on the chains: {}, see the errors above; nothing was broadcasted",
+                chains.join(", ")
+            ),
+            Error::FailedVerifications(contracts) => {
+                write!(f, "The verification failed for: {}, see the errors above", contracts.join(", "))
+            }
+            Error::PartialDeployment { chain, deployed, remaining } => write!(
+                f,
+                "For chain {}, the deployment is partial, so it was not broadcasted; deployed: {}; remaining: {}",
+                chain,
+                deployed.join(", "),
+                remaining.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Spawn { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use super::{
+    broadcast::{self, BroadcastFile, BroadcastRun},
+    chain_data::ChainRegistry,
+    config::Config,
+    error::Error,
+    manifest, report,
+    report::ReportFormat,
+    typescript,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+/// The format broadcast files are exported in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// The broadcast file as written by forge.
+    #[default]
+    Full,
+    /// Only the deployment details, without bytecode, calldata or logs.
+    Slim,
+}
+
+impl ExportFormat {
+    /// Parses a format name as passed on the command line.
+    pub fn parse(name: &str) -> Result<Self, Error> {
+        match name {
+            "full" => Ok(ExportFormat::Full),
+            "slim" => Ok(ExportFormat::Slim),
+            _ => Err(Error::InvalidArgument(format!("Unknown export format {}, expected full or slim", name))),
+        }
+    }
+
+    /// Returns the content to export for a broadcast file read from `path`.
+    pub fn render(
+        &self,
+        broadcast: &str,
+        path: &Path,
+    ) -> Result<String, Error> {
+        match self {
+            ExportFormat::Full => Ok(broadcast.to_string()),
+            ExportFormat::Slim => slim_broadcast(broadcast, path),
+        }
+    }
+}
+
+/// Exports the deployments of a script, read from its broadcasts, to the SDK, Markdown reports, JSON manifests and
+/// TypeScript deployments, as configured in `deployer.toml`.
+pub struct Exporter<'a> {
+    config: &'a Config,
+    script_name: &'a str,
+    chain_ids: &'a HashMap<String, u64>,
+    run: BroadcastRun,
+}
+
+impl<'a> Exporter<'a> {
+    /// Creates an exporter for the latest broadcasts of a script on chains whose ids are known.
+    pub fn new(
+        config: &'a Config,
+        script_name: &'a str,
+        chain_ids: &'a HashMap<String, u64>,
+    ) -> Self {
+        Exporter { config, script_name, chain_ids, run: BroadcastRun::Latest }
+    }
+
+    /// Exports another run of the script than the latest broadcasted one.
+    pub fn with_run(
+        mut self,
+        run: BroadcastRun,
+    ) -> Self {
+        self.run = run;
+        self
+    }
+
+    /// Returns the broadcast file of the exported run on a chain.
+    pub fn broadcast(
+        &self,
+        chain: &str,
+    ) -> BroadcastFile {
+        BroadcastFile::new(self.script_name, chain, self.chain_ids[chain], true).with_run(&self.run)
+    }
+
+    /// Copies a broadcast file to the SDK, see `BroadcastFile::copy_broadcast_file`.
+    pub fn copy(
+        &self,
+        broadcast: &BroadcastFile,
+        force: bool,
+    ) -> Result<(), Error> {
+        broadcast.copy_broadcast_file(&self.config.sdk, &self.config.projects, force)
+    }
+
+    /// Renders the deployments on the given chains, see `report::render`.
+    pub fn report(
+        &self,
+        format: ReportFormat,
+        chains: &[String],
+        skip_missing: bool,
+    ) -> Result<String, Error> {
+        report::render(format, self.script_name, &self.run, chains, self.chain_ids, skip_missing)
+    }
+
+    /// Merges the deployments on the given chains into the JSON manifest at `path`, see `manifest::update_manifest`.
+    pub fn manifest(
+        &self,
+        path: &Path,
+        chains: &[String],
+        skip_missing: bool,
+    ) -> Result<(), Error> {
+        let contracts = self.config.contract_settings(self.script_name)?;
+        let deployments =
+            broadcast::read_deployments(self.script_name, &self.run, chains, self.chain_ids, skip_missing)?;
+        manifest::update_manifest(path, self.script_name, &self.config.projects, &deployments, &contracts)
+    }
+
+    /// Compares the addresses of the contracts across the given chains, and describes the contracts deployed at
+    /// different addresses, e.g. when CREATE2 salts differ between chains. zkEVM chains are left out, as their CREATE2
+    /// addresses are derived differently.
+    pub fn address_mismatches(
+        &self,
+        chains: &[String],
+    ) -> Result<Vec<String>, Error> {
+        let mut addresses: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        for (chain, chain_id, broadcast) in
+            broadcast::read_deployments(self.script_name, &self.run, chains, self.chain_ids, true)?
+        {
+            if ChainRegistry::is_zkevm(chain_id) {
+                continue;
+            }
+            for contract in broadcast.contracts {
+                // A contract deployed more than once on a chain is compared by its first address
+                let Some(name) = contract.contract_name else {
+                    continue;
+                };
+                let deployments = addresses.entry(name).or_default();
+                if !deployments.iter().any(|(deployed_chain, _)| *deployed_chain == chain) {
+                    deployments.push((chain.clone(), contract.contract_address.to_lowercase()));
+                }
+            }
+        }
+
+        Ok(addresses
+            .into_iter()
+            .filter(|(_, deployments)| deployments.iter().any(|(_, address)| *address != deployments[0].1))
+            .map(|(name, deployments)| {
+                let deployments: Vec<String> =
+                    deployments.iter().map(|(chain, address)| format!("{} on {}", address, chain)).collect();
+                format!("{} has different addresses across chains: {}", name, deployments.join(", "))
+            })
+            .collect())
+    }
+
+    /// Updates the entries of the given chains in the TypeScript deployments at `path`, see
+    /// `typescript::update_deployments_file`.
+    pub fn typescript(
+        &self,
+        path: &Path,
+        chains: &[String],
+    ) -> Result<(), Error> {
+        let contracts = self.config.contract_settings(self.script_name)?;
+        typescript::update_deployments_file(path, self.script_name, &self.run, chains, self.chain_ids, &contracts)
+    }
+}
+
+/// A broadcast stripped down to what the SDK needs. Fields are serialized in declaration order, so that diffs between
+/// releases only show actual changes.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlimBroadcast {
+    pub chain: Option<u64>,
+    pub timestamp: Option<u64>,
+    pub deployer: Option<String>,
+    pub contracts: Vec<SlimContract>,
+}
+
+/// A contract created by the broadcast.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlimContract {
+    pub contract_name: Option<String>,
+    pub contract_address: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<u64>,
+    pub arguments: Vec<Value>,
+    /// The contract that created it, for contracts created by another contract, e.g. by a CREATE2 factory. Forge lists
+    /// them as the `additionalContracts` of a transaction, without their constructor arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory: Option<String>,
+}
+
+impl SlimBroadcast {
+    /// Extracts the deployment details from a parsed forge broadcast file.
+    pub fn from_json(json: &Value) -> Self {
+        let transactions = json.get("transactions").and_then(Value::as_array).cloned().unwrap_or_default();
+        let receipts = json.get("receipts").and_then(Value::as_array).cloned().unwrap_or_default();
+
+        let deployer = transactions
+            .iter()
+            .find_map(|tx| tx.get("transaction").and_then(|t| t.get("from")).and_then(Value::as_str))
+            .map(String::from);
+
+        let contracts = transactions
+            .iter()
+            .flat_map(|tx| {
+                let contract_address = tx.get("contractAddress").and_then(Value::as_str).map(String::from);
+                let transaction_hash = tx.get("hash").and_then(Value::as_str).map(String::from);
+
+                // Receipts are matched to their transaction by hash
+                let block_number = receipts
+                    .iter()
+                    .find(|receipt| {
+                        transaction_hash.is_some() &&
+                            receipt.get("transactionHash").and_then(Value::as_str) == transaction_hash.as_deref()
+                    })
+                    .and_then(|receipt| receipt.get("blockNumber"))
+                    .and_then(parse_quantity);
+
+                // Calls also hold the address of the contract they target, which only creates contracts of its own
+                let is_call = tx.get("transactionType").and_then(Value::as_str) == Some("CALL");
+                let created = contract_address.clone().filter(|_| !is_call).map(|contract_address| SlimContract {
+                    contract_name: tx.get("contractName").and_then(Value::as_str).map(String::from),
+                    contract_address,
+                    transaction_hash: transaction_hash.clone(),
+                    block_number,
+                    arguments: tx.get("arguments").and_then(Value::as_array).cloned().unwrap_or_default(),
+                    factory: None,
+                });
+
+                let additional = tx.get("additionalContracts").and_then(Value::as_array).cloned().unwrap_or_default();
+                let additional = additional.into_iter().filter_map(move |contract| {
+                    Some(SlimContract {
+                        contract_name: contract.get("contractName").and_then(Value::as_str).map(String::from),
+                        contract_address: contract.get("address").and_then(Value::as_str)?.to_string(),
+                        transaction_hash: transaction_hash.clone(),
+                        block_number,
+                        arguments: Vec::new(),
+                        factory: contract_address.clone(),
+                    })
+                });
+
+                created.into_iter().chain(additional)
+            })
+            .collect();
+
+        SlimBroadcast {
+            chain: json.get("chain").and_then(Value::as_u64),
+            timestamp: json.get("timestamp").and_then(Value::as_u64),
+            deployer,
+            contracts,
+        }
+    }
+}
+
+/// Converts a forge broadcast file, read from `path`, into its slim JSON representation.
+pub fn slim_broadcast(
+    broadcast: &str,
+    path: &Path,
+) -> Result<String, Error> {
+    let parse_error = |e: serde_json::Error| Error::Parse { path: path.to_path_buf(), message: e.to_string() };
+    let json: Value = serde_json::from_str(broadcast).map_err(parse_error)?;
+
+    let mut content = serde_json::to_string_pretty(&SlimBroadcast::from_json(&json)).map_err(parse_error)?;
+    content.push('\n');
+    Ok(content)
+}
+
+/// Parses a JSON-RPC quantity, which forge writes as a hex string, e.g. `"0x1669749"`.
+pub fn parse_quantity(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(hex) => u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok(),
+        Value::Number(number) => number.as_u64(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROADCAST: &str = r#"{
+  "transactions": [
+    {
+      "hash": "0x01",
+      "transactionType": "CREATE",
+      "contractName": "SablierLockup",
+      "contractAddress": "0x00000000000000000000000000000000000000aa",
+      "arguments": ["0x00000000000000000000000000000000000000ad"],
+      "transaction": { "from": "0x00000000000000000000000000000000000000ad", "input": "0x6080" },
+      "additionalContracts": []
+    },
+    {
+      "hash": "0x02",
+      "transactionType": "CALL",
+      "contractName": "SablierFactory",
+      "contractAddress": "0x00000000000000000000000000000000000000fa",
+      "arguments": null,
+      "transaction": { "from": "0x00000000000000000000000000000000000000ad", "input": "0x1234" },
+      "additionalContracts": [
+        {
+          "transactionType": "CREATE2",
+          "contractName": "SablierMerkleLL",
+          "address": "0x00000000000000000000000000000000000000bb",
+          "initCode": "0x6080"
+        }
+      ]
+    }
+  ],
+  "receipts": [
+    { "transactionHash": "0x02", "blockNumber": "0x2", "logsBloom": "0x00" },
+    { "transactionHash": "0x01", "blockNumber": "0x1", "logsBloom": "0x00" }
+  ],
+  "libraries": [],
+  "timestamp": 1700000000,
+  "chain": 11155111
+}"#;
+
+    #[test]
+    fn slims_a_broadcast_down_to_its_deployments() {
+        let slim = slim_broadcast(BROADCAST, Path::new("run-latest.json")).unwrap();
+
+        // The call is skipped, but not the contract its target created
+        let expected = r#"{
+  "chain": 11155111,
+  "timestamp": 1700000000,
+  "deployer": "0x00000000000000000000000000000000000000ad",
+  "contracts": [
+    {
+      "contractName": "SablierLockup",
+      "contractAddress": "0x00000000000000000000000000000000000000aa",
+      "transactionHash": "0x01",
+      "blockNumber": 1,
+      "arguments": [
+        "0x00000000000000000000000000000000000000ad"
+      ]
+    },
+    {
+      "contractName": "SablierMerkleLL",
+      "contractAddress": "0x00000000000000000000000000000000000000bb",
+      "transactionHash": "0x02",
+      "blockNumber": 2,
+      "arguments": [],
+      "factory": "0x00000000000000000000000000000000000000fa"
+    }
+  ]
+}
+"#;
+        assert_eq!(slim, expected);
+        assert!(!slim.contains("input") && !slim.contains("logsBloom") && !slim.contains("initCode"));
+    }
+}
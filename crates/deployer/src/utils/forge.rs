@@ -0,0 +1,247 @@
+use super::error::Error;
+use ethabi::{
+    encode,
+    param_type::Reader,
+    short_signature,
+    token::{LenientTokenizer, Tokenizer},
+    ParamType,
+};
+use hex::encode as hex_encode;
+
+// The directory deployment scripts are read from when none is provided.
+pub const DEFAULT_SCRIPT_DIR: &str = "script";
+
+/// Describes how the deployment script is invoked with `forge script`.
+#[derive(Debug)]
+pub struct ScriptOptions {
+    /// The directory containing the script, relative to the project root.
+    pub dir: String,
+    /// The script file name, e.g. `DeployFlow.s.sol`. Foundry names the broadcast directory after it.
+    pub name: String,
+    /// The contract to run within the script file, when the file declares more than one.
+    pub contract: Option<String>,
+    /// The function signature to call, e.g. `run(address)`.
+    pub sig: Option<String>,
+    /// The arguments of the function, ABI-encoded together with the signature.
+    pub sig_args: Vec<String>,
+    /// Extra arguments passed as-is to `forge script`.
+    pub extra_args: Vec<String>,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        ScriptOptions {
+            dir: DEFAULT_SCRIPT_DIR.to_string(),
+            name: String::new(),
+            contract: None,
+            sig: None,
+            sig_args: Vec::new(),
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl ScriptOptions {
+    /// Returns the script target, e.g. `scripts/solidity/DeployFlow.s.sol:DeployFlow`.
+    pub fn target(&self) -> String {
+        let path = format!("{}/{}", self.dir.trim_end_matches('/'), self.name);
+        match &self.contract {
+            Some(contract) => format!("{}:{}", path, contract),
+            None => path,
+        }
+    }
+
+    /// Returns the leading `forge script` arguments, i.e. the target and the signature.
+    ///
+    /// When arguments are provided, the signature and its arguments are ABI-encoded into calldata, which `--sig`
+    /// accepts in place of a signature.
+    pub fn args(&self) -> Result<Vec<String>, Error> {
+        let mut args = vec![self.target()];
+
+        match &self.sig {
+            Some(sig) if !self.sig_args.is_empty() => {
+                args.push("--sig".to_string());
+                args.push(encode_calldata(sig, &self.sig_args)?);
+            }
+            Some(sig) => {
+                args.push("--sig".to_string());
+                args.push(sig.to_string());
+            }
+            None if !self.sig_args.is_empty() => {
+                return Err(Error::InvalidArgument(
+                    "Script arguments were provided without a signature, pass it with --sig".to_string(),
+                ));
+            }
+            None => {}
+        }
+
+        Ok(args)
+    }
+}
+
+// Encodes a function call, e.g. `run(address,uint256)` with `["0x..", "5"]`, into hex calldata.
+fn encode_calldata(
+    sig: &str,
+    values: &[String],
+) -> Result<String, Error> {
+    let (name, params) = parse_signature(sig)?;
+    if params.len() != values.len() {
+        return Err(Error::InvalidArgument(format!(
+            "The signature {} expects {} arguments, {} were provided",
+            sig,
+            params.len(),
+            values.len()
+        )));
+    }
+
+    let tokens = params
+        .iter()
+        .zip(values)
+        .map(|(param, value)| {
+            LenientTokenizer::tokenize(param, value)
+                .map_err(|e| Error::InvalidArgument(format!("Failed to encode argument {} as {}: {}", value, param, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut calldata = short_signature(&name, &params).to_vec();
+    calldata.extend(encode(&tokens));
+    Ok(format!("0x{}", hex_encode(calldata)))
+}
+
+// Splits a signature such as `run(address,(uint256,bool))` into its name and parameter types.
+fn parse_signature(sig: &str) -> Result<(String, Vec<ParamType>), Error> {
+    let invalid =
+        || Error::InvalidArgument(format!("Invalid function signature {}, expected e.g. run(address,uint256)", sig));
+
+    let trimmed = sig.trim();
+    let open = trimmed.find('(').ok_or_else(invalid)?;
+    let name = trimmed[..open].trim().to_string();
+    if !trimmed.ends_with(')') || name.is_empty() {
+        return Err(invalid());
+    }
+    let inner = &trimmed[open + 1..trimmed.len() - 1];
+
+    // Split on the commas that are not nested within a tuple
+    let mut types = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or_else(invalid)?,
+            ',' if depth == 0 => {
+                types.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(invalid());
+    }
+    if !inner.trim().is_empty() {
+        types.push(&inner[start..]);
+    }
+
+    let params = types
+        .iter()
+        .map(|t| {
+            // Types can't contain whitespace, so it is dropped, e.g. within `(uint256, bool)`
+            let t: String = t.split_whitespace().collect();
+            // The reader takes an empty type for a `uint8`, e.g. in `run(address,)`
+            if t.is_empty() || [",,", "(,", ",)"].iter().any(|empty| t.contains(empty)) {
+                return Err(Error::InvalidArgument(format!("Missing parameter type in {}", sig)));
+            }
+            Reader::read(&t)
+                .map_err(|e| Error::InvalidArgument(format!("Invalid parameter type {} in {}: {}", t, sig, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((name, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADMIN: &str = "0xb1bef51ebca01eb12001a639bdbbff6eeca12b9f";
+
+    fn assert_invalid(result: Result<impl std::fmt::Debug, Error>) {
+        assert!(matches!(result, Err(Error::InvalidArgument(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn parses_nested_tuples_and_arrays_of_tuples() {
+        let (name, params) = parse_signature("run(address,(uint256,(bool,bytes32)),(address,uint128)[])").unwrap();
+
+        assert_eq!(name, "run");
+        assert_eq!(
+            params,
+            [
+                ParamType::Address,
+                ParamType::Tuple(vec![
+                    ParamType::Uint(256),
+                    ParamType::Tuple(vec![ParamType::Bool, ParamType::FixedBytes(32)]),
+                ]),
+                ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(128)]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_the_whitespace_around_and_within_the_types() {
+        let spaced = parse_signature(" run ( address , ( uint256 , bool ) [] ) ").unwrap();
+        let compact = parse_signature("run(address,(uint256,bool)[])").unwrap();
+
+        assert_eq!(spaced, compact);
+        assert_eq!(parse_signature("run( )").unwrap(), ("run".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        for sig in [
+            "run",
+            "(address)",
+            " (address)",
+            "run(address",
+            "run((uint256,bool)",
+            "run(uint256,bool))",
+            "run(address))(",
+            "run(address,,uint256)",
+            "run(address,)",
+            "run((uint256,))",
+        ] {
+            assert_invalid(parse_signature(sig));
+        }
+    }
+
+    #[test]
+    fn encodes_the_calldata_of_a_signature() {
+        let calldata = encode_calldata("transfer(address,uint256)", &[ADMIN.to_string(), "5".to_string()]).unwrap();
+
+        // The selector of `transfer(address,uint256)`, followed by the padded arguments
+        let expected = format!("0xa9059cbb{:0>64}{:0>64}", ADMIN.trim_start_matches("0x"), "5");
+        assert_eq!(calldata, expected);
+    }
+
+    #[test]
+    fn encodes_arrays_of_tuples() {
+        let values = [format!("[({},1),({},2)]", ADMIN, ADMIN)];
+        let calldata = encode_calldata("run((address,uint256)[])", &values).unwrap();
+
+        let tuple = |amount: u8| format!("{:0>64}{:0>64x}", ADMIN.trim_start_matches("0x"), amount);
+        let selector = hex_encode(short_signature(
+            "run",
+            &[ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(256)])))],
+        ));
+        // The offset of the array, its length and its elements, which are encoded in place as they are static
+        let expected = format!("0x{}{:0>64x}{:0>64x}{}{}", selector, 32, 2, tuple(1), tuple(2));
+        assert_eq!(calldata, expected);
+    }
+
+    #[test]
+    fn rejects_arguments_that_dont_match_the_signature() {
+        assert_invalid(encode_calldata("run(address,uint256)", &[ADMIN.to_string()]));
+        assert_invalid(encode_calldata("run(uint256)", &["not a number".to_string()]));
+        assert_invalid(encode_calldata("run((uint256,bool)", &["(1,true)".to_string()]));
+    }
+}
@@ -0,0 +1,375 @@
+use super::{
+    chain_data::ChainRegistry,
+    error::{Error, UnknownChain},
+    toolchain::ToolchainRunner,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+};
+use toml::{value::Table, Value as TomlValue};
+
+// The Foundry configuration file, looked up at the root of the project.
+pub const FOUNDRY_FILE: &str = "foundry.toml";
+
+// Where the artifacts of the zkEVM builds are written, relative to the project root.
+pub const ZKEVM_OUT_DIR: &str = "zkout";
+
+// The profile every other profile inherits from.
+const DEFAULT_PROFILE: &str = "default";
+
+/// An `[etherscan]` entry, used by Foundry to verify contracts on a chain's explorer.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EtherscanConfig {
+    /// The API key, possibly referencing environment variables as `${VAR}`.
+    pub key: String,
+    /// The verifier URL, for explorers that are not resolved by Foundry from the chain.
+    pub url: Option<String>,
+    /// The chain, either as a name or as a chain id.
+    pub chain: Option<TomlValue>,
+}
+
+/// The parts of `foundry.toml` the deployer relies on, resolved for a single profile.
+#[derive(Debug)]
+pub struct FoundryConfig {
+    /// The profile the configuration was resolved for.
+    pub profile: String,
+    /// The `[rpc_endpoints]` entries, with the URLs left unexpanded.
+    pub rpc_endpoints: BTreeMap<String, String>,
+    /// The `[etherscan]` entries, keyed by chain name.
+    pub etherscan: BTreeMap<String, EtherscanConfig>,
+}
+
+impl FoundryConfig {
+    /// Reads `foundry.toml` from the current directory and resolves it for the given profile.
+    ///
+    /// The `${VAR}` references are expanded from the `.env` file of the project, falling back to the process
+    /// environment.
+    pub fn load(profile: &str) -> Result<Self, Error> {
+        let path = Path::new(FOUNDRY_FILE);
+        if !path.exists() {
+            return Err(Error::MissingFile {
+                path: path.to_path_buf(),
+                hint: "run the deployer from the root of a Foundry project".to_string(),
+            });
+        }
+        let toml_content = fs::read_to_string(path).map_err(|source| Error::Io { path: path.to_path_buf(), source })?;
+
+        // Variables already set in the environment take precedence over the ones declared in `.env`
+        dotenv::dotenv().ok();
+
+        Self::parse(&toml_content, profile)
+    }
+
+    // Parses the content of a `foundry.toml` file and resolves it for the given profile.
+    fn parse(
+        toml_content: &str,
+        profile: &str,
+    ) -> Result<Self, Error> {
+        let root: Table = toml::from_str(toml_content)
+            .map_err(|e| Error::Parse { path: PathBuf::from(FOUNDRY_FILE), message: e.to_string() })?;
+
+        let profiles = root.get("profile").and_then(TomlValue::as_table).cloned().unwrap_or_default();
+        if profile != DEFAULT_PROFILE && !profiles.contains_key(profile) {
+            println!("Profile {} is not declared in {}, using the default profile settings\n", profile, FOUNDRY_FILE);
+        }
+
+        // Every profile inherits the keys it doesn't set from the default profile
+        let mut settings = profiles.get(DEFAULT_PROFILE).and_then(TomlValue::as_table).cloned().unwrap_or_default();
+        if profile != DEFAULT_PROFILE {
+            if let Some(overrides) = profiles.get(profile).and_then(TomlValue::as_table) {
+                merge(&mut settings, overrides);
+            }
+        }
+
+        // Standalone sections apply to every profile, and can be refined within a profile
+        let rpc_endpoints: BTreeMap<String, String> = section(&root, &settings, "rpc_endpoints")?;
+        let etherscan: BTreeMap<String, EtherscanConfig> = section(&root, &settings, "etherscan")?;
+
+        Ok(FoundryConfig { profile: profile.to_string(), rpc_endpoints, etherscan })
+    }
+
+    /// Returns the chains declared in `[rpc_endpoints]`, except `localhost`.
+    pub fn chains(&self) -> Vec<String> {
+        self.rpc_endpoints.keys().filter(|&key| key != "localhost").cloned().collect()
+    }
+
+    /// Returns the environment variables of the Foundry commands, which select the profile.
+    pub fn env(&self) -> Vec<(String, String)> {
+        vec![("FOUNDRY_PROFILE".to_string(), self.profile.clone())]
+    }
+
+    /// Returns the environment variables of the Foundry commands run against a chain. The artifacts of zkEVM chains
+    /// are built apart from the EVM ones, so that both builds stay cached.
+    pub fn chain_env(
+        &self,
+        chain_id: u64,
+    ) -> Vec<(String, String)> {
+        let mut env = self.env();
+        if ChainRegistry::is_zkevm(chain_id) {
+            env.push(("FOUNDRY_OUT".to_string(), ZKEVM_OUT_DIR.to_string()));
+        }
+        env
+    }
+
+    /// Returns the RPC URL of a chain, with the `${VAR}` references expanded.
+    pub fn rpc_url(
+        &self,
+        chain: &str,
+    ) -> Result<String, Error> {
+        let url = self.rpc_endpoints.get(chain).ok_or_else(|| {
+            Error::UnknownChains(vec![UnknownChain { chain: chain.to_string(), suggestions: Vec::new() }])
+        })?;
+        self.expand(url, &format!("The RPC URL of chain {}", chain))
+    }
+
+    /// Returns the id of a chain, looked up in the chain registry first, then in the `chain` field of its `[etherscan]`
+    /// entry. Chains known to neither are asked for their id through their RPC URL, with `cast chain-id` run by the
+    /// given toolchain.
+    pub fn chain_id(
+        &self,
+        chain: &str,
+        runner: &dyn ToolchainRunner,
+    ) -> Result<u64, Error> {
+        if let Some(chain_id) = ChainRegistry::chain_id(chain) {
+            return Ok(chain_id);
+        }
+        if let Some(TomlValue::Integer(id)) = self.etherscan.get(chain).and_then(|entry| entry.chain.as_ref()) {
+            if let Ok(chain_id) = u64::try_from(*id) {
+                return Ok(chain_id);
+            }
+        }
+
+        let output = runner.chain_id(&self.rpc_url(chain)?)?;
+        match output.stdout.trim().parse() {
+            Ok(chain_id) if output.success => Ok(chain_id),
+            _ => Err(Error::Rpc {
+                chain: chain.to_string(),
+                message: format!("cast chain-id failed with: {}", output.stderr.trim()),
+            }),
+        }
+    }
+
+    /// Returns the ids of the given chains, by chain name, see `chain_id`.
+    pub fn chain_ids(
+        &self,
+        chains: &[String],
+        runner: &dyn ToolchainRunner,
+    ) -> Result<HashMap<String, u64>, Error> {
+        chains.iter().map(|chain| Ok((chain.clone(), self.chain_id(chain, runner)?))).collect()
+    }
+
+    /// Returns the `[etherscan]` entry of a chain, if any. Entries are matched by their name first, then by their
+    /// `chain` field, which can hold either the chain name or its id.
+    pub fn etherscan(
+        &self,
+        chain: &str,
+        chain_id: u64,
+    ) -> Option<&EtherscanConfig> {
+        self.etherscan.get(chain).or_else(|| {
+            self.etherscan.values().find(|entry| match &entry.chain {
+                Some(TomlValue::String(name)) => name == chain,
+                Some(TomlValue::Integer(id)) => u64::try_from(*id) == Ok(chain_id),
+                _ => false,
+            })
+        })
+    }
+
+    /// Expands the `${VAR}` references in a value. The context describes the value in the error returned when a
+    /// variable is not set, e.g. "The RPC URL of chain mainnet".
+    pub fn expand(
+        &self,
+        value: &str,
+        context: &str,
+    ) -> Result<String, Error> {
+        let mut expanded = String::new();
+        let mut rest = value;
+
+        while let Some(start) = rest.find("${") {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            let name = &rest[start + 2..end];
+            let var = Self::var(name)
+                .ok_or_else(|| Error::MissingVariable { name: name.to_string(), context: context.to_string() })?;
+
+            expanded.push_str(&rest[..start]);
+            expanded.push_str(&var);
+            rest = &rest[end + 1..];
+        }
+        expanded.push_str(rest);
+
+        Ok(expanded)
+    }
+
+    /// Returns the value of an environment variable, including the ones declared in `.env`.
+    pub fn var(name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+}
+
+// Merges the entries of a profile section into the section it inherits from, table by table.
+fn merge(
+    base: &mut Table,
+    overrides: &Table,
+) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(TomlValue::Table(base_table)), TomlValue::Table(override_table)) => merge(base_table, override_table),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+// Reads a standalone section, such as `[rpc_endpoints]`, refined by the entries set in the resolved profile.
+fn section<T: for<'de> Deserialize<'de>>(
+    root: &Table,
+    settings: &Table,
+    name: &str,
+) -> Result<BTreeMap<String, T>, Error> {
+    let mut table = root.get(name).and_then(TomlValue::as_table).cloned().unwrap_or_default();
+    if let Some(profile_table) = settings.get(name).and_then(TomlValue::as_table) {
+        merge(&mut table, profile_table);
+    }
+
+    TomlValue::Table(table).try_into().map_err(|e| Error::Parse {
+        path: PathBuf::from(FOUNDRY_FILE),
+        message: format!("invalid [{}] section: {}", name, e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOUNDRY_TOML: &str = r#"
+[profile.default]
+src = "src"
+optimizer = { enabled = true, runs = 1000 }
+
+[profile.optimized]
+optimizer = { runs = 5000 }
+
+[profile.optimized.rpc_endpoints]
+mainnet = "https://optimized.example.com"
+abstract = "https://api.mainnet.abs.xyz"
+
+[rpc_endpoints]
+localhost = "http://localhost:8545"
+mainnet = "https://mainnet.example.com/${DEPLOYER_TEST_FOUNDRY_RPC_KEY}"
+sepolia = "https://sepolia.example.com"
+
+[etherscan]
+mainnet = { key = "${DEPLOYER_TEST_FOUNDRY_API_KEY}" }
+mode = { key = "mode-key", url = "https://explorer.mode.network/api", chain = 34443 }
+polygon_explorer = { key = "polygon-key", chain = "polygon" }
+"#;
+
+    fn rpc_endpoints(config: &FoundryConfig) -> Vec<(&str, &str)> {
+        config.rpc_endpoints.iter().map(|(chain, url)| (chain.as_str(), url.as_str())).collect()
+    }
+
+    #[test]
+    fn inherits_the_default_profile_table_by_table() {
+        let mut base: Table = toml::from_str("optimizer = { enabled = true, runs = 1000 }\nsrc = \"src\"").unwrap();
+        let overrides: Table = toml::from_str("optimizer = { runs = 5000 }\nvia_ir = true").unwrap();
+
+        merge(&mut base, &overrides);
+
+        let expected: Table =
+            toml::from_str("optimizer = { enabled = true, runs = 5000 }\nsrc = \"src\"\nvia_ir = true").unwrap();
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn refines_the_sections_with_the_profile() {
+        let default = FoundryConfig::parse(FOUNDRY_TOML, "default").unwrap();
+        assert_eq!(
+            rpc_endpoints(&default),
+            [
+                ("localhost", "http://localhost:8545"),
+                ("mainnet", "https://mainnet.example.com/${DEPLOYER_TEST_FOUNDRY_RPC_KEY}"),
+                ("sepolia", "https://sepolia.example.com"),
+            ]
+        );
+        assert_eq!(default.chains(), ["mainnet", "sepolia"]);
+
+        let optimized = FoundryConfig::parse(FOUNDRY_TOML, "optimized").unwrap();
+        assert_eq!(optimized.profile, "optimized");
+        assert_eq!(
+            rpc_endpoints(&optimized),
+            [
+                ("abstract", "https://api.mainnet.abs.xyz"),
+                ("localhost", "http://localhost:8545"),
+                ("mainnet", "https://optimized.example.com"),
+                ("sepolia", "https://sepolia.example.com"),
+            ]
+        );
+        assert_eq!(optimized.env(), [("FOUNDRY_PROFILE".to_string(), "optimized".to_string())]);
+
+        // An undeclared profile falls back to the default settings
+        let undeclared = FoundryConfig::parse(FOUNDRY_TOML, "undeclared").unwrap();
+        assert_eq!(rpc_endpoints(&undeclared), rpc_endpoints(&default));
+    }
+
+    #[test]
+    fn rejects_invalid_sections() {
+        let error = FoundryConfig::parse("[rpc_endpoints]\nmainnet = 1\n", "default").unwrap_err();
+        assert!(
+            matches!(error, Error::Parse { ref message, .. } if message.contains("[rpc_endpoints]")),
+            "{:?}",
+            error
+        );
+
+        assert!(matches!(FoundryConfig::parse("[rpc_endpoints", "default"), Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn expands_the_variables_of_the_values() {
+        let config = FoundryConfig::parse(FOUNDRY_TOML, "default").unwrap();
+        env::set_var("DEPLOYER_TEST_FOUNDRY_RPC_KEY", "secret");
+
+        assert_eq!(config.rpc_url("mainnet").unwrap(), "https://mainnet.example.com/secret");
+        assert_eq!(
+            config.expand("${DEPLOYER_TEST_FOUNDRY_RPC_KEY}-${DEPLOYER_TEST_FOUNDRY_RPC_KEY}", "").unwrap(),
+            "secret-secret"
+        );
+        assert_eq!(config.expand("no variable", "").unwrap(), "no variable");
+
+        // An unterminated reference is kept as is
+        assert_eq!(config.expand("https://example.com/${KEY", "").unwrap(), "https://example.com/${KEY");
+        assert_eq!(config.expand("${DEPLOYER_TEST_FOUNDRY_RPC_KEY}/${KEY", "").unwrap(), "secret/${KEY");
+    }
+
+    #[test]
+    fn reports_the_variables_that_are_not_set() {
+        let config = FoundryConfig::parse(FOUNDRY_TOML, "default").unwrap();
+
+        let error = config.expand("${DEPLOYER_TEST_FOUNDRY_UNSET}", "The API key of chain mainnet").unwrap_err();
+
+        assert!(
+            matches!(error, Error::MissingVariable { ref name, ref context }
+                if name == "DEPLOYER_TEST_FOUNDRY_UNSET" && context == "The API key of chain mainnet"),
+            "{:?}",
+            error
+        );
+        assert!(matches!(config.rpc_url("arbitrum"), Err(Error::UnknownChains(_))));
+    }
+
+    #[test]
+    fn looks_up_explorers_by_name_then_by_chain() {
+        let config = FoundryConfig::parse(FOUNDRY_TOML, "default").unwrap();
+
+        let key = |chain: &str, chain_id: u64| config.etherscan(chain, chain_id).map(|entry| entry.key.as_str());
+        assert_eq!(key("mainnet", 1), Some("${DEPLOYER_TEST_FOUNDRY_API_KEY}"));
+        assert_eq!(key("mode", 1), Some("mode-key"));
+        assert_eq!(key("polygon", 137), Some("polygon-key"));
+        assert_eq!(key("mode_mainnet", 34443), Some("mode-key"));
+        assert_eq!(key("sepolia", 11155111), None);
+    }
+}
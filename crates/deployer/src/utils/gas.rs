@@ -0,0 +1,251 @@
+use super::error::Error;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// The number of wei in a gwei.
+const WEI_PER_GWEI: u128 = 1_000_000_000;
+// The number of wei in an ether.
+const WEI_PER_ETHER: u128 = 1_000_000_000_000_000_000;
+
+/// Gas settings for a chain. Prices accept a `wei`, `gwei` or `ether` suffix, and are read as wei without one.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GasSettings {
+    /// The gas price used by forge for the simulation, passed as `--gas-price`.
+    pub gas_price: Option<String>,
+    /// The gas price of the broadcasted transactions, passed as `--with-gas-price`.
+    pub with_gas_price: Option<String>,
+    /// The priority fee of the broadcasted transactions, passed as `--priority-gas-price`.
+    pub priority_gas_price: Option<String>,
+    /// The percentage by which the gas estimates are multiplied, passed as `--gas-estimate-multiplier`.
+    pub gas_estimate_multiplier: Option<u64>,
+}
+
+impl GasSettings {
+    /// Returns the settings with the values set in `overrides` taking precedence.
+    pub fn merge(
+        &self,
+        overrides: &GasSettings,
+    ) -> GasSettings {
+        GasSettings {
+            gas_price: overrides.gas_price.clone().or_else(|| self.gas_price.clone()),
+            with_gas_price: overrides.with_gas_price.clone().or_else(|| self.with_gas_price.clone()),
+            priority_gas_price: overrides.priority_gas_price.clone().or_else(|| self.priority_gas_price.clone()),
+            gas_estimate_multiplier: overrides.gas_estimate_multiplier.or(self.gas_estimate_multiplier),
+        }
+    }
+
+    /// Returns the `forge script` flags for the settings, with the prices converted to wei.
+    pub fn args(&self) -> Result<Vec<String>, Error> {
+        let mut args = Vec::new();
+
+        let prices = [
+            ("--gas-price", &self.gas_price),
+            ("--with-gas-price", &self.with_gas_price),
+            ("--priority-gas-price", &self.priority_gas_price),
+        ];
+        for (flag, price) in prices {
+            if let Some(price) = price {
+                args.push(flag.to_string());
+                args.push(parse_wei(price)?.to_string());
+            }
+        }
+
+        if let Some(multiplier) = self.gas_estimate_multiplier {
+            args.push("--gas-estimate-multiplier".to_string());
+            args.push(multiplier.to_string());
+        }
+
+        Ok(args)
+    }
+}
+
+/// Gas settings provided on the command line, either for every chain or for a single one.
+#[derive(Debug, Default)]
+pub struct GasOverrides {
+    /// Settings applied to every chain, e.g. `--gas-price 2gwei`.
+    pub global: GasSettings,
+    /// Settings applied to a single chain, e.g. `--gas-price chiliz=10000gwei`.
+    pub per_chain: HashMap<String, GasSettings>,
+}
+
+impl GasOverrides {
+    /// Records a gas flag value, given as `<value>` or `<chain>=<value>`. Values are validated right away.
+    pub fn set(
+        &mut self,
+        flag: &str,
+        value: &str,
+    ) -> Result<(), Error> {
+        let (settings, value) = match value.split_once('=') {
+            Some((chain, value)) => (self.per_chain.entry(chain.to_string()).or_default(), value),
+            None => (&mut self.global, value),
+        };
+
+        match flag {
+            "--gas-price" => settings.gas_price = Some(checked_price(value)?),
+            "--with-gas-price" => settings.with_gas_price = Some(checked_price(value)?),
+            "--priority-gas-price" => settings.priority_gas_price = Some(checked_price(value)?),
+            "--gas-estimate-multiplier" => {
+                let multiplier = value.parse().map_err(|_| {
+                    Error::InvalidArgument(format!("Invalid gas estimate multiplier {}, expected e.g. 130", value))
+                })?;
+                settings.gas_estimate_multiplier = Some(multiplier);
+            }
+            _ => return Err(Error::InvalidArgument(format!("Unknown gas flag {}", flag))),
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the settings of a chain: chain-specific flags take precedence over global flags, which take precedence
+    /// over the chain's `[gas.<chain>]` settings from `deployer.toml`.
+    pub fn resolve(
+        &self,
+        chain: &str,
+        configured: Option<&GasSettings>,
+    ) -> GasSettings {
+        let settings = configured.cloned().unwrap_or_default().merge(&self.global);
+        match self.per_chain.get(chain) {
+            Some(overrides) => settings.merge(overrides),
+            None => settings,
+        }
+    }
+}
+
+fn checked_price(value: &str) -> Result<String, Error> {
+    parse_wei(value).map(|_| value.to_string())
+}
+
+// Parses a price such as `1000`, `1000wei`, `2gwei`, `0.5 gwei` or `0.001ether` into wei.
+fn parse_wei(value: &str) -> Result<u128, Error> {
+    let invalid = || {
+        Error::InvalidArgument(format!(
+            "Invalid gas price {}, expected a value in wei, gwei or ether, e.g. 1gwei",
+            value
+        ))
+    };
+
+    let trimmed = value.trim().to_lowercase();
+    let (amount, multiplier) = if let Some(amount) = trimmed.strip_suffix("ether") {
+        (amount.trim(), WEI_PER_ETHER)
+    } else if let Some(amount) = trimmed.strip_suffix("gwei") {
+        (amount.trim(), WEI_PER_GWEI)
+    } else if let Some(amount) = trimmed.strip_suffix("wei") {
+        (amount.trim(), 1)
+    } else {
+        (trimmed.as_str(), 1)
+    };
+
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if integer.is_empty() || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    // Fractions are only meaningful down to a single wei
+    let decimals = multiplier.ilog10() as usize;
+    if fraction.len() > decimals {
+        return Err(invalid());
+    }
+    let fraction = format!("{:0<width$}", fraction, width = decimals);
+
+    let integer: u128 = integer.parse().map_err(|_| invalid())?;
+    let fraction: u128 = if fraction.is_empty() { 0 } else { fraction.parse().map_err(|_| invalid())? };
+
+    integer.checked_mul(multiplier).and_then(|wei| wei.checked_add(fraction)).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(gas_price: &str) -> GasSettings {
+        GasSettings { gas_price: Some(gas_price.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn parses_prices_in_wei_gwei_and_ether() {
+        assert_eq!(parse_wei("1000").unwrap(), 1000);
+        assert_eq!(parse_wei("1000wei").unwrap(), 1000);
+        assert_eq!(parse_wei("2gwei").unwrap(), 2_000_000_000);
+        assert_eq!(parse_wei(" 10000 GWei ").unwrap(), 10_000_000_000_000);
+        assert_eq!(parse_wei("1ether").unwrap(), 1_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn parses_decimals_down_to_a_single_wei() {
+        assert_eq!(parse_wei("0.5 gwei").unwrap(), 500_000_000);
+        assert_eq!(parse_wei("1.000000001gwei").unwrap(), 1_000_000_001);
+        assert_eq!(parse_wei("0.001ether").unwrap(), 1_000_000_000_000_000);
+        assert_eq!(parse_wei("2.gwei").unwrap(), 2_000_000_000);
+
+        assert!(parse_wei("1.5").is_err());
+        assert!(parse_wei("1.5wei").is_err());
+        assert!(parse_wei("0.0000000001gwei").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_and_overflowing_prices() {
+        for price in ["", "gwei", ".5gwei", "-1gwei", "1 eth", "1e9", "0x10", "1.2.3gwei"] {
+            assert!(parse_wei(price).is_err(), "{:?} should be rejected", price);
+        }
+
+        assert_eq!(parse_wei(&u128::MAX.to_string()).unwrap(), u128::MAX);
+        assert!(parse_wei(&format!("{}0", u128::MAX)).is_err());
+        assert!(parse_wei(&format!("{}gwei", u128::MAX / WEI_PER_GWEI + 1)).is_err());
+        assert!(parse_wei(&format!("{}ether", u128::MAX / WEI_PER_ETHER + 1)).is_err());
+    }
+
+    #[test]
+    fn records_flags_for_every_chain_or_a_single_one() {
+        let mut overrides = GasOverrides::default();
+        overrides.set("--gas-price", "2gwei").unwrap();
+        overrides.set("--priority-gas-price", "chiliz=1gwei").unwrap();
+        overrides.set("--gas-estimate-multiplier", "linea=150").unwrap();
+
+        assert_eq!(overrides.global.gas_price.as_deref(), Some("2gwei"));
+        assert_eq!(overrides.per_chain["chiliz"].priority_gas_price.as_deref(), Some("1gwei"));
+        assert_eq!(overrides.per_chain["linea"].gas_estimate_multiplier, Some(150));
+
+        assert!(overrides.set("--gas-price", "chiliz=2gwie").is_err());
+        assert!(overrides.set("--gas-estimate-multiplier", "1.5").is_err());
+        assert!(overrides.set("--gas-limit", "100").is_err());
+    }
+
+    #[test]
+    fn resolves_chain_flags_over_global_flags_over_the_configuration() {
+        let configured = GasSettings {
+            gas_price: Some("1gwei".to_string()),
+            with_gas_price: Some("10000gwei".to_string()),
+            priority_gas_price: Some("1gwei".to_string()),
+            gas_estimate_multiplier: Some(150),
+        };
+        let mut overrides = GasOverrides::default();
+        overrides.set("--gas-price", "2gwei").unwrap();
+        overrides.set("--with-gas-price", "3gwei").unwrap();
+        overrides.set("--with-gas-price", "chiliz=20000gwei").unwrap();
+
+        let chiliz = overrides.resolve("chiliz", Some(&configured));
+        assert_eq!(chiliz.gas_price.as_deref(), Some("2gwei"));
+        assert_eq!(chiliz.with_gas_price.as_deref(), Some("20000gwei"));
+        assert_eq!(chiliz.priority_gas_price.as_deref(), Some("1gwei"));
+        assert_eq!(chiliz.gas_estimate_multiplier, Some(150));
+
+        let sepolia = overrides.resolve("sepolia", None);
+        assert_eq!(sepolia.with_gas_price.as_deref(), Some("3gwei"));
+        assert_eq!(sepolia.priority_gas_price, None);
+        assert_eq!(sepolia.gas_estimate_multiplier, None);
+
+        assert_eq!(
+            GasOverrides::default().resolve("linea", Some(&settings("5gwei"))).gas_price.as_deref(),
+            Some("5gwei")
+        );
+    }
+
+    #[test]
+    fn converts_the_settings_to_forge_flags_in_wei() {
+        let settings = GasSettings { gas_estimate_multiplier: Some(130), ..settings("0.5gwei") };
+
+        assert_eq!(settings.args().unwrap(), ["--gas-price", "500000000", "--gas-estimate-multiplier", "130"]);
+        assert!(GasSettings::default().args().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,358 @@
+use super::{broadcast, error::Error, export::SlimBroadcast, naming::ContractSettings, package::Package};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::ErrorKind,
+    path::Path,
+};
+
+/// The JSON Schema manifests are validated against, published in the `schema` directory of the deployer.
+pub const SCHEMA: &str = include_str!("../../schema/manifest.schema.json");
+
+/// Where the manifest is written when no path is provided.
+pub const DEFAULT_MANIFEST_FILE: &str = "deployments.json";
+
+/// The contracts deployed by a release of a project, for indexers and frontends.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Manifest {
+    pub project: String,
+    pub version: String,
+    /// The deployments of each chain, keyed by chain id.
+    pub chains: BTreeMap<u64, ChainManifest>,
+}
+
+/// The contracts deployed on a chain, keyed by contract name.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChainManifest {
+    pub contracts: BTreeMap<String, ContractManifest>,
+}
+
+/// A deployed contract.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractManifest {
+    pub address: String,
+    pub block: u64,
+    pub tx_hash: String,
+}
+
+impl Manifest {
+    /// Reads the manifest at `path` and validates it against the schema. Returns `None` when there is no file yet.
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(source) if source.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(Error::Io { path: path.to_path_buf(), source }),
+        };
+
+        let parse_error = |message: String| Error::Parse { path: path.to_path_buf(), message };
+        let json: Value = serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?;
+        validate(&json).map_err(parse_error)?;
+        serde_json::from_value(json).map(Some).map_err(|e| parse_error(e.to_string()))
+    }
+
+    /// Replaces the deployments of a chain with the contracts created by its broadcast, except those the contract
+    /// settings exclude and the unnamed ones created by another contract. When a contract is created more than once,
+    /// only its first deployment is kept.
+    pub fn insert(
+        &mut self,
+        chain: &str,
+        chain_id: u64,
+        broadcast: &SlimBroadcast,
+        settings: &ContractSettings,
+    ) -> Result<(), Error> {
+        let mut contracts = BTreeMap::new();
+
+        for contract in &broadcast.contracts {
+            // Forge doesn't always know the name of the contracts created by another contract
+            let name = match (&contract.contract_name, &contract.factory) {
+                (Some(name), _) => name.clone(),
+                (None, Some(factory)) => {
+                    println!(
+                        "For chain {}, the contract at {} created by {} has no name, so it is left out",
+                        chain, contract.contract_address, factory
+                    );
+                    continue;
+                }
+                (None, None) => {
+                    return Err(Error::UnnamedContract {
+                        chain: chain.to_string(),
+                        address: contract.contract_address.clone(),
+                    });
+                }
+            };
+            if !settings.includes(chain, &name) {
+                continue;
+            }
+            let (Some(block), Some(tx_hash)) = (contract.block_number, contract.transaction_hash.clone()) else {
+                return Err(Error::MissingReceipt { chain: chain.to_string(), contract: name });
+            };
+
+            if contracts.contains_key(&name) {
+                println!(
+                    "For chain {}, {} was deployed more than once, only its first deployment is kept",
+                    chain, name
+                );
+                continue;
+            }
+            contracts.insert(name, ContractManifest { address: contract.contract_address.clone(), block, tx_hash });
+        }
+
+        self.chains.insert(chain_id, ChainManifest { contracts });
+        Ok(())
+    }
+
+    /// Validates the manifest against the schema and writes it to `path`.
+    pub fn write(
+        &self,
+        path: &Path,
+    ) -> Result<(), Error> {
+        let invalid = |message: String| Error::InvalidManifest { path: path.to_path_buf(), message };
+        let json = serde_json::to_value(self).map_err(|e| invalid(e.to_string()))?;
+        validate(&json).map_err(invalid)?;
+
+        // The manifest is written from its fields, as the JSON value would sort the chain ids as strings
+        let mut content = serde_json::to_string_pretty(self).map_err(|e| invalid(e.to_string()))?;
+        content.push('\n');
+        broadcast::write_atomically(path, &content)
+    }
+}
+
+/// Builds the manifest of the deployments of a script, as read by `broadcast::read_deployments`, and merges it into the
+/// manifest at `path`.
+///
+/// The chains of the existing manifest that are not part of these deployments are kept as they are. An existing
+/// manifest must be for the same project and version.
+pub fn update_manifest(
+    path: &Path,
+    script_name: &str,
+    projects: &HashMap<String, String>,
+    deployments: &[(String, u64, SlimBroadcast)],
+    contracts: &ContractSettings,
+) -> Result<(), Error> {
+    let package = Package::load()?;
+    let project = package.project(script_name, projects)?;
+
+    merge_manifest(path, project, package.version, deployments, contracts)?;
+    println!("Wrote the manifest to {}", path.display());
+    Ok(())
+}
+
+// Merges the deployments into the manifest of a release of a project at `path`, see `update_manifest`.
+fn merge_manifest(
+    path: &Path,
+    project: String,
+    version: String,
+    deployments: &[(String, u64, SlimBroadcast)],
+    contracts: &ContractSettings,
+) -> Result<(), Error> {
+    let mut manifest = match Manifest::load(path)? {
+        Some(existing) if existing.project != project || existing.version != version => {
+            return Err(Error::ManifestMismatch {
+                path: path.to_path_buf(),
+                existing: format!("{} v{}", existing.project, existing.version),
+                expected: format!("{} v{}", project, version),
+            });
+        }
+        Some(existing) => existing,
+        None => Manifest { project, version, chains: BTreeMap::new() },
+    };
+
+    for (chain, chain_id, broadcast) in deployments {
+        manifest.insert(chain, *chain_id, broadcast, contracts)?;
+    }
+
+    manifest.write(path)
+}
+
+// Validates a manifest against the schema, returning the violations as a single message.
+fn validate(json: &Value) -> Result<(), String> {
+    let schema: Value = serde_json::from_str(SCHEMA).map_err(|e| format!("invalid schema: {}", e))?;
+    let schema = JSONSchema::compile(&schema).map_err(|e| format!("invalid schema: {}", e))?;
+
+    schema.validate(json).map_err(|errors| {
+        errors.map(|error| format!("{} at \"{}\"", error, error.instance_path)).collect::<Vec<_>>().join(", ")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::export::SlimContract;
+    use std::{env, path::PathBuf, process};
+
+    // A manifest path of its own for each test, as the tests run concurrently.
+    fn manifest_path(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("deployer-manifest-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(DEFAULT_MANIFEST_FILE)
+    }
+
+    fn contract(
+        name: Option<&str>,
+        n: u8,
+        block_number: Option<u64>,
+    ) -> SlimContract {
+        SlimContract {
+            contract_name: name.map(String::from),
+            contract_address: format!("0x{}", format!("{:02x}", n).repeat(20)),
+            transaction_hash: Some(format!("0x{}", format!("{:02x}", n).repeat(32))),
+            block_number,
+            arguments: Vec::new(),
+            factory: None,
+        }
+    }
+
+    fn deployment(
+        chain: &str,
+        chain_id: u64,
+        contracts: Vec<SlimContract>,
+    ) -> (String, u64, SlimBroadcast) {
+        let broadcast = SlimBroadcast { chain: Some(chain_id), timestamp: None, deployer: None, contracts };
+        (chain.to_string(), chain_id, broadcast)
+    }
+
+    fn contract_names(
+        manifest: &Manifest,
+        chain_id: u64,
+    ) -> Vec<&str> {
+        manifest.chains[&chain_id].contracts.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn merges_the_deployments_keeping_the_other_chains() {
+        let path = manifest_path("merge");
+        let settings = ContractSettings::default();
+        let first = [
+            deployment("mainnet", 1, vec![contract(Some("SablierLockup"), 1, Some(100))]),
+            deployment("sepolia", 11155111, vec![contract(Some("SablierLockup"), 2, Some(200))]),
+        ];
+        merge_manifest(&path, "lockup".to_string(), "2.0.0".to_string(), &first, &settings).unwrap();
+
+        let second = [deployment(
+            "sepolia",
+            11155111,
+            vec![
+                contract(Some("SablierBatchLockup"), 3, Some(300)),
+                contract(Some("SablierBatchLockup"), 4, Some(400)),
+            ],
+        )];
+        merge_manifest(&path, "lockup".to_string(), "2.0.0".to_string(), &second, &settings).unwrap();
+
+        let manifest = Manifest::load(&path).unwrap().unwrap();
+        assert_eq!(contract_names(&manifest, 1), ["SablierLockup"]);
+        // The chain of the new run is replaced, keeping the first deployment of a contract created twice
+        assert_eq!(contract_names(&manifest, 11155111), ["SablierBatchLockup"]);
+        let batch_lockup = &manifest.chains[&11155111].contracts["SablierBatchLockup"];
+        assert_eq!(batch_lockup.address, format!("0x{}", "03".repeat(20)));
+        assert_eq!(batch_lockup.block, 300);
+        assert_eq!(batch_lockup.tx_hash, format!("0x{}", "03".repeat(32)));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rejects_a_manifest_of_another_project_or_version() {
+        let path = manifest_path("mismatch");
+        let settings = ContractSettings::default();
+        let deployments = [deployment("mainnet", 1, vec![contract(Some("SablierFlow"), 1, Some(100))])];
+        merge_manifest(&path, "flow".to_string(), "1.1.0".to_string(), &deployments, &settings).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+
+        for (project, version) in [("lockup", "1.1.0"), ("flow", "1.2.0")] {
+            let error =
+                merge_manifest(&path, project.to_string(), version.to_string(), &deployments, &settings).unwrap_err();
+            assert!(
+                matches!(error, Error::ManifestMismatch { ref existing, ref expected, .. }
+                    if existing == "flow v1.1.0" && *expected == format!("{} v{}", project, version)),
+                "{:?}",
+                error
+            );
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rejects_contracts_without_a_name_or_a_receipt() {
+        let mut manifest =
+            Manifest { project: "lockup".to_string(), version: "2.0.0".to_string(), chains: BTreeMap::new() };
+        let settings = ContractSettings::default();
+
+        let (chain, chain_id, unnamed) = deployment("mainnet", 1, vec![contract(None, 1, Some(100))]);
+        let error = manifest.insert(&chain, chain_id, &unnamed, &settings).unwrap_err();
+        assert!(matches!(error, Error::UnnamedContract { ref chain, .. } if chain == "mainnet"), "{:?}", error);
+
+        let (chain, chain_id, dry_run) = deployment("mainnet", 1, vec![contract(Some("SablierLockup"), 1, None)]);
+        let error = manifest.insert(&chain, chain_id, &dry_run, &settings).unwrap_err();
+        assert!(
+            matches!(error, Error::MissingReceipt { ref contract, .. } if contract == "SablierLockup"),
+            "{:?}",
+            error
+        );
+
+        // An unnamed contract created by a factory is left out
+        let mut created = contract(None, 2, Some(100));
+        created.factory = Some(format!("0x{}", "fa".repeat(20)));
+        let (chain, chain_id, factory) = deployment("mainnet", 1, vec![created]);
+        manifest.insert(&chain, chain_id, &factory, &settings).unwrap();
+        assert!(manifest.chains[&1].contracts.is_empty());
+    }
+
+    #[test]
+    fn writes_the_chains_in_numeric_order() {
+        let path = manifest_path("order");
+        let deployments: Vec<_> = [("optimism", 10), ("mainnet", 1), ("polygon", 137), ("base", 8453)]
+            .into_iter()
+            .map(|(chain, chain_id)| deployment(chain, chain_id, vec![contract(Some("SablierLockup"), 1, Some(1))]))
+            .collect();
+
+        merge_manifest(&path, "lockup".to_string(), "2.0.0".to_string(), &deployments, &ContractSettings::default())
+            .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let positions: Vec<usize> =
+            ["\"1\"", "\"10\"", "\"137\"", "\"8453\""].iter().map(|id| content.find(id).unwrap()).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", content);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn validates_the_manifests_against_the_shipped_schema() {
+        let path = manifest_path("schema");
+        let deployments = [deployment("mainnet", 1, vec![contract(Some("SablierLockup"), 1, Some(100))])];
+        merge_manifest(&path, "lockup".to_string(), "2.0.0".to_string(), &deployments, &ContractSettings::default())
+            .unwrap();
+
+        // The generated manifest matches the schema published in the `schema` directory
+        let schema_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("schema/manifest.schema.json");
+        let schema: Value = serde_json::from_str(&fs::read_to_string(schema_path).unwrap()).unwrap();
+        let schema = JSONSchema::compile(&schema).unwrap();
+        let manifest: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(schema.is_valid(&manifest));
+
+        // Manifests that don't match it are rejected, whether they are read or written
+        fs::write(&path, r#"{"project":"lockup","version":"2.0.0","chains":{"mainnet":{"contracts":{}}}}"#).unwrap();
+        assert!(matches!(Manifest::load(&path), Err(Error::Parse { .. })));
+
+        let contracts = BTreeMap::from([(
+            "SablierLockup".to_string(),
+            ContractManifest { address: "0x1234".to_string(), block: 100, tx_hash: format!("0x{}", "01".repeat(32)) },
+        )]);
+        let invalid = Manifest {
+            project: "lockup".to_string(),
+            version: "2.0.0".to_string(),
+            chains: BTreeMap::from([(1, ChainManifest { contracts })]),
+        };
+        let error = invalid.write(&path).unwrap_err();
+        assert!(
+            matches!(error, Error::InvalidManifest { ref message, .. } if message.contains("0x1234")),
+            "{:?}",
+            error
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// What to do with deployed contracts that are not in the contract list of a project.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownContracts {
+    /// Leave them out of the generated files, e.g. helper libraries.
+    #[default]
+    Exclude,
+    /// Keep them, with a warning.
+    Warn,
+}
+
+/// How the contracts of a project appear in generated files, see `[contracts.<project>]` in `deployer.toml`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContractSettings {
+    /// The contracts of the project. When empty, every deployed contract is kept.
+    pub list: Vec<String>,
+    /// Manifest keys overriding the converted contract names, e.g. `SablierNFTDescriptor = "NFT_DESCRIPTOR"`.
+    pub keys: HashMap<String, String>,
+    /// What to do with the deployed contracts that are not in `list`.
+    pub unknown: UnknownContracts,
+}
+
+impl ContractSettings {
+    /// Returns the manifest key of a contract, e.g. `SablierLockup` -> `SABLIER_LOCKUP`.
+    pub fn key(
+        &self,
+        contract_name: &str,
+    ) -> String {
+        self.keys.get(contract_name).cloned().unwrap_or_else(|| manifest_key(contract_name))
+    }
+
+    /// Whether a contract deployed on a chain goes into generated files. Contracts that are not in the list are
+    /// reported, whether they are kept or not.
+    pub fn includes(
+        &self,
+        chain: &str,
+        contract_name: &str,
+    ) -> bool {
+        if self.list.is_empty() || self.list.iter().any(|name| name == contract_name) {
+            return true;
+        }
+
+        match self.unknown {
+            UnknownContracts::Exclude => {
+                println!(
+                    "For chain {}, {} is not in the contract list of the project, leaving it out",
+                    chain, contract_name
+                );
+                false
+            }
+            UnknownContracts::Warn => {
+                println!("For chain {}, {} is not in the contract list of the project", chain, contract_name);
+                true
+            }
+        }
+    }
+}
+
+/// Converts a contract name to upper snake case, keeping acronyms and versions together, e.g. `SablierMerkleLL` ->
+/// `SABLIER_MERKLE_LL` and `SablierV2NFTDescriptor` -> `SABLIER_V2_NFT_DESCRIPTOR`.
+///
+/// A word starts at an uppercase letter following a lowercase letter or a digit, and at the last uppercase letter of
+/// an acronym followed by a lowercase letter.
+pub fn manifest_key(contract_name: &str) -> String {
+    let chars: Vec<char> = contract_name.chars().collect();
+    let mut key = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let previous = chars[i - 1];
+            let next = chars.get(i + 1).copied();
+            let ends_acronym = previous.is_uppercase() && next.is_some_and(char::is_lowercase);
+            if previous.is_lowercase() || previous.is_ascii_digit() || ends_acronym {
+                key.push('_');
+            }
+        }
+        key.extend(c.to_uppercase());
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_contract_names() {
+        let cases = [
+            ("SablierLockup", "SABLIER_LOCKUP"),
+            ("SablierBatchLockup", "SABLIER_BATCH_LOCKUP"),
+            ("SablierMerkleLL", "SABLIER_MERKLE_LL"),
+            ("SablierMerkleLT", "SABLIER_MERKLE_LT"),
+            ("SablierMerkleVCA", "SABLIER_MERKLE_VCA"),
+            ("SablierV2NFTDescriptor", "SABLIER_V2_NFT_DESCRIPTOR"),
+            ("SablierV2LockupLinear", "SABLIER_V2_LOCKUP_LINEAR"),
+            ("LockupNFTDescriptor", "LOCKUP_NFT_DESCRIPTOR"),
+            ("ERC20Mock", "ERC20_MOCK"),
+            ("Helpers", "HELPERS"),
+        ];
+        for (name, key) in cases {
+            assert_eq!(manifest_key(name), key, "{}", name);
+        }
+    }
+
+    #[test]
+    fn prefers_overrides_and_filters_unknown_contracts() {
+        let mut settings = ContractSettings {
+            list: vec!["SablierLockup".to_string(), "SablierNFTDescriptor".to_string()],
+            keys: HashMap::from([("SablierNFTDescriptor".to_string(), "NFT_DESCRIPTOR".to_string())]),
+            unknown: UnknownContracts::Exclude,
+        };
+        assert_eq!(settings.key("SablierNFTDescriptor"), "NFT_DESCRIPTOR");
+        assert_eq!(settings.key("SablierLockup"), "SABLIER_LOCKUP");
+        assert!(settings.includes("mainnet", "SablierLockup"));
+        assert!(!settings.includes("mainnet", "Helpers"));
+
+        settings.unknown = UnknownContracts::Warn;
+        assert!(settings.includes("mainnet", "Helpers"));
+    }
+}
@@ -0,0 +1,127 @@
+use super::error::Error;
+use serde_json::Value;
+use std::{collections::HashMap, fs, io::ErrorKind, path::Path};
+
+// The npm manifest, looked up at the root of the project.
+const PACKAGE_FILE: &str = "package.json";
+
+/// The fields of `package.json` the deployer relies on.
+#[derive(Debug)]
+pub struct Package {
+    /// The project, derived from the package name by dropping its scope, e.g. `@sablier/flow` -> `flow`.
+    pub project: Option<String>,
+    /// The version of the package, e.g. `2.0.0`.
+    pub version: String,
+}
+
+impl Package {
+    /// Reads `package.json` from the current directory.
+    pub fn load() -> Result<Self, Error> {
+        Self::read(Path::new(PACKAGE_FILE))
+    }
+
+    // Reads the package manifest at `path`.
+    fn read(path: &Path) -> Result<Self, Error> {
+        let path = path.to_path_buf();
+        let content = fs::read_to_string(&path).map_err(|source| match source.kind() {
+            ErrorKind::NotFound => Error::MissingFile {
+                path: path.clone(),
+                hint: "run the deployer from the root of an npm project".to_string(),
+            },
+            _ => Error::Io { path: path.clone(), source },
+        })?;
+        let package: Value =
+            serde_json::from_str(&content).map_err(|e| Error::Parse { path: path.clone(), message: e.to_string() })?;
+
+        let version = package
+            .get("version")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::Parse { path: path.clone(), message: "missing the \"version\" field".to_string() })?
+            .to_string();
+        let project = package.get("name").and_then(Value::as_str).map(project_from_package_name);
+
+        Ok(Package { project, version })
+    }
+
+    /// Returns the project a script belongs to, looked up in the `[projects]` mapping of `deployer.toml` first, then
+    /// derived from the package name.
+    pub fn project(
+        &self,
+        script_name: &str,
+        projects: &HashMap<String, String>,
+    ) -> Result<String, Error> {
+        projects
+            .get(script_name)
+            .or(self.project.as_ref())
+            .cloned()
+            .ok_or_else(|| Error::UnknownProject { script: script_name.to_string() })
+    }
+}
+
+// Derives the project from an npm package name by dropping its scope, e.g. `@sablier/flow` -> `flow`.
+fn project_from_package_name(name: &str) -> String {
+    name.rsplit('/').next().unwrap_or(name).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    // Writes a package manifest of its own for each test, as the tests run concurrently.
+    fn read_package(
+        name: &str,
+        content: &str,
+    ) -> Result<Package, Error> {
+        let dir = env::temp_dir().join(format!("deployer-package-{}-{}", name, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(PACKAGE_FILE);
+        fs::write(&path, content).unwrap();
+
+        let package = Package::read(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        package
+    }
+
+    #[test]
+    fn derives_the_project_from_the_package_name() {
+        assert_eq!(project_from_package_name("@sablier/flow"), "flow");
+        assert_eq!(project_from_package_name("lockup"), "lockup");
+
+        let package = read_package("scoped", r#"{ "name": "@sablier/flow", "version": "1.1.0" }"#).unwrap();
+        assert_eq!(package.project.as_deref(), Some("flow"));
+        assert_eq!(package.version, "1.1.0");
+    }
+
+    #[test]
+    fn prefers_the_projects_of_the_config_to_the_package_name() {
+        let package = Package { project: Some("lockup".to_string()), version: "2.0.0".to_string() };
+        let projects = HashMap::from([("DeployMerkleFactories.s.sol".to_string(), "airdrops".to_string())]);
+
+        assert_eq!(package.project("DeployMerkleFactories.s.sol", &projects).unwrap(), "airdrops");
+        assert_eq!(package.project("DeployProtocol.s.sol", &projects).unwrap(), "lockup");
+    }
+
+    #[test]
+    fn requires_a_project_for_unnamed_packages() {
+        let package = read_package("unnamed", r#"{ "version": "2.0.0" }"#).unwrap();
+        let projects = HashMap::from([("DeployMerkleFactories.s.sol".to_string(), "airdrops".to_string())]);
+
+        assert_eq!(package.project("DeployMerkleFactories.s.sol", &projects).unwrap(), "airdrops");
+        let error = package.project("DeployProtocol.s.sol", &projects).unwrap_err();
+        assert!(
+            matches!(error, Error::UnknownProject { ref script } if script == "DeployProtocol.s.sol"),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn requires_the_version_of_the_package() {
+        let error = read_package("unversioned", r#"{ "name": "@sablier/flow" }"#).unwrap_err();
+        assert!(matches!(error, Error::Parse { ref message, .. } if message.contains("version")), "{:?}", error);
+
+        let error = Package::read(&env::temp_dir().join("deployer-package-missing").join(PACKAGE_FILE)).unwrap_err();
+        assert!(matches!(error, Error::MissingFile { .. }), "{:?}", error);
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+// A bucket of tokens refilled at a constant rate, up to one second of requests.
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Limits the rate of requests made with each key, such as an explorer API key, with a token bucket per key. Each
+/// bucket allows bursts of up to one second of requests. The limiter can be shared between threads.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing the given number of requests per second with each key. A rate that is not positive
+    /// disables the limit.
+    pub fn new(requests_per_second: f64) -> Self {
+        RateLimiter { requests_per_second, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// Waits until a request can be made with a key, and takes its token.
+    pub fn acquire(
+        &self,
+        key: &str,
+    ) {
+        if self.requests_per_second <= 0.0 {
+            return;
+        }
+
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let now = Instant::now();
+                let bucket =
+                    buckets.entry(key.to_string()).or_insert(TokenBucket { tokens: self.capacity(), refilled_at: now });
+
+                let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.capacity());
+                bucket.refilled_at = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.requests_per_second)
+            };
+
+            // The lock is released while waiting, so that requests with other keys go through
+            thread::sleep(wait);
+        }
+    }
+
+    // The most tokens a bucket holds, which is at least one so that slow rates still allow a request.
+    fn capacity(&self) -> f64 {
+        self.requests_per_second.max(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_each_key_separately() {
+        let limiter = RateLimiter::new(20.0);
+        let start = Instant::now();
+
+        // The first second of requests is a burst, then the tokens come at the rate of the limit
+        for _ in 0..25 {
+            limiter.acquire("etherscan");
+        }
+        let limited = start.elapsed();
+        assert!(limited >= Duration::from_millis(200), "25 requests at 20/s took {:?}", limited);
+
+        let start = Instant::now();
+        for _ in 0..20 {
+            limiter.acquire("blockscout");
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
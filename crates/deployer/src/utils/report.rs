@@ -0,0 +1,217 @@
+use super::{
+    broadcast::{self, BroadcastRun},
+    chain_data::ChainRegistry,
+    error::Error,
+    export::SlimBroadcast,
+};
+use std::collections::HashMap;
+
+/// The format deployment reports are rendered in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReportFormat {
+    /// One Markdown table per chain, for docs and release notes.
+    #[default]
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Parses a format name as passed on the command line.
+    pub fn parse(name: &str) -> Result<Self, Error> {
+        match name {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            _ => Err(Error::InvalidArgument(format!("Unknown report format {}, expected markdown", name))),
+        }
+    }
+}
+
+// The deployments of a script on a chain.
+struct ChainReport {
+    name: String,
+    chain_id: u64,
+    explorer_url: Option<&'static str>,
+    is_testnet: bool,
+    broadcast: SlimBroadcast,
+}
+
+/// Renders the deployments of a script on the given chains, read from the broadcasts of a run.
+///
+/// Chains are named after the chain registry and grouped into mainnets and testnets. Chains without a broadcast are
+/// skipped when `skip_missing` is set, e.g. when reporting on all chains; otherwise they are an error.
+pub fn render(
+    format: ReportFormat,
+    script_name: &str,
+    run: &BroadcastRun,
+    chains: &[String],
+    chain_ids: &HashMap<String, u64>,
+    skip_missing: bool,
+) -> Result<String, Error> {
+    let deployments = broadcast::read_deployments(script_name, run, chains, chain_ids, skip_missing)?;
+    let reports = chain_reports(deployments);
+
+    match format {
+        ReportFormat::Markdown => Ok(markdown(&reports)),
+    }
+}
+
+// Looks up the chains of the deployments in the registry, sorted by name.
+fn chain_reports(deployments: Vec<(String, u64, SlimBroadcast)>) -> Vec<ChainReport> {
+    let mut reports: Vec<ChainReport> = deployments
+        .into_iter()
+        .map(|(chain, chain_id, broadcast)| {
+            let data = ChainRegistry::get(chain_id);
+            ChainReport {
+                name: data.map_or_else(|| chain.clone(), |data| data.name.to_string()),
+                chain_id,
+                explorer_url: data.map(|data| data.explorer_url),
+                is_testnet: data.map_or_else(|| is_testnet_name(&chain), |data| data.is_testnet),
+                broadcast,
+            }
+        })
+        .collect();
+    reports.sort_by_key(|report| report.name.to_lowercase());
+    reports
+}
+
+// Renders one section per chain, mainnets first.
+fn markdown(reports: &[ChainReport]) -> String {
+    let mut lines = Vec::new();
+
+    for (title, is_testnet) in [("Mainnets", false), ("Testnets", true)] {
+        let group: Vec<&ChainReport> = reports.iter().filter(|report| report.is_testnet == is_testnet).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        lines.push(format!("## {}\n", title));
+        for report in group {
+            lines.push(format!("### {} ({})\n", report.name, report.chain_id));
+            lines.push("| Contract | Address | Block | Transaction |".to_string());
+            lines.push("| :------- | :------ | ----: | :---------- |".to_string());
+
+            for contract in &report.broadcast.contracts {
+                let address =
+                    link(report.explorer_url, "address", &contract.contract_address, &contract.contract_address);
+                let block = contract.block_number.map_or_else(|| "-".to_string(), |block| block.to_string());
+                let transaction = contract
+                    .transaction_hash
+                    .as_deref()
+                    .map_or_else(|| "-".to_string(), |hash| link(report.explorer_url, "tx", &shorten(hash), hash));
+                lines.push(format!(
+                    "| {} | {} | {} | {} |",
+                    contract.contract_name.as_deref().unwrap_or("Unknown"),
+                    address,
+                    block,
+                    transaction
+                ));
+            }
+            lines.push(String::new());
+        }
+    }
+
+    lines.join("\n")
+}
+
+// Links a value to its page on the explorer, e.g. `https://etherscan.io/tx/0x..`, when the explorer is known.
+fn link(
+    explorer_url: Option<&str>,
+    kind: &str,
+    text: &str,
+    value: &str,
+) -> String {
+    match explorer_url {
+        Some(url) => format!("[{}]({}/{}/{})", text, url.trim_end_matches('/'), kind, value),
+        None => format!("`{}`", text),
+    }
+}
+
+// Shortens a transaction hash to its first and last bytes, e.g. `0x1234…cdef`.
+fn shorten(hash: &str) -> String {
+    if hash.len() <= 14 {
+        return hash.to_string();
+    }
+    format!("{}…{}", &hash[..6], &hash[hash.len() - 4..])
+}
+
+// Guesses whether a chain that is not in the registry is a testnet from its name.
+fn is_testnet_name(chain: &str) -> bool {
+    ["sepolia", "testnet", "holesky", "hoodi"].iter().any(|testnet| chain.contains(testnet))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::export::SlimContract;
+    use std::{fs, path::PathBuf};
+
+    const LOCKUP: &str = "0xcF8ce57fa442ba50aCbC57147a62aD03873FfA73";
+    const BATCH_LOCKUP: &str = "0x3F6E8a8Cffe377c4649aCeB01e6F20c60fAA356c";
+    const HASH: &str = "0x5c4c3e5d7e6b3f3bd4d4d4c1c1b1a1f0e0d0c0b0a09080706050403020100ff";
+
+    // A contract with a receipt when it has a block, i.e. when it was broadcast.
+    fn contract(
+        name: Option<&str>,
+        address: &str,
+        block: Option<u64>,
+    ) -> SlimContract {
+        SlimContract {
+            contract_name: name.map(String::from),
+            contract_address: address.to_string(),
+            transaction_hash: block.map(|_| HASH.to_string()),
+            block_number: block,
+            arguments: Vec::new(),
+            factory: None,
+        }
+    }
+
+    fn deployment(
+        chain: &str,
+        chain_id: u64,
+        contracts: Vec<SlimContract>,
+    ) -> (String, u64, SlimBroadcast) {
+        let broadcast = SlimBroadcast { chain: Some(chain_id), timestamp: None, deployer: None, contracts };
+        (chain.to_string(), chain_id, broadcast)
+    }
+
+    // Compares the report to the expected file under `testdata/report`. Set `UPDATE_GOLDEN=1` to rewrite it instead.
+    #[test]
+    fn renders_a_markdown_table_per_chain() {
+        let deployments = vec![
+            deployment("sepolia", 11155111, vec![contract(Some("SablierLockup"), LOCKUP, Some(7600000))]),
+            deployment(
+                "mainnet",
+                1,
+                vec![
+                    contract(Some("SablierLockup"), LOCKUP, Some(21719029)),
+                    contract(Some("SablierBatchLockup"), BATCH_LOCKUP, Some(21719030)),
+                ],
+            ),
+            // Chains unknown to the registry have no explorer, and dry runs have no receipts
+            deployment("devnet", 1337, vec![contract(None, LOCKUP, None)]),
+            deployment("arbitrum", 42161, vec![contract(Some("SablierLockup"), LOCKUP, Some(300000000))]),
+            deployment("base_sepolia", 84532, vec![contract(Some("SablierLockup"), LOCKUP, Some(20000000))]),
+        ];
+
+        let report = markdown(&chain_reports(deployments));
+
+        let expected_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/report/expected.md");
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            fs::write(&expected_path, &report).unwrap();
+        }
+        assert_eq!(report, fs::read_to_string(expected_path).unwrap());
+    }
+
+    #[test]
+    fn shortens_transaction_hashes() {
+        assert_eq!(shorten(HASH), "0x5c4c…00ff");
+        assert_eq!(shorten("0x1234"), "0x1234");
+    }
+
+    #[test]
+    fn links_values_to_the_explorer() {
+        assert_eq!(
+            link(Some("https://etherscan.io/"), "tx", "0x12…34", "0x1234"),
+            "[0x12…34](https://etherscan.io/tx/0x1234)"
+        );
+        assert_eq!(link(None, "address", "0x1234", "0x1234"), "`0x1234`");
+    }
+}
@@ -0,0 +1,243 @@
+use super::error::{Error, UnknownChain};
+use std::collections::HashMap;
+
+/// Resolves the chains to deploy to from the command-line selection.
+///
+/// Each requested entry can be a chain name, a group declared in `deployer.toml` or a glob such as `*_sepolia`.
+/// When `on_all_chains` is set, the selection starts from every available chain. Excluded entries are resolved the
+/// same way and removed afterwards. Only chains declared in `foundry.toml` are kept, in the order they were requested.
+///
+/// Chain names passed explicitly, either requested or excluded, must be declared in `foundry.toml`; otherwise an error
+/// suggesting the closest declared names is returned. Group members that are not declared are silently skipped, so
+/// groups can be shared between projects. Falling back to a default chain is left to the caller.
+pub fn select_chains(
+    requested: &[String],
+    excluded: &[String],
+    on_all_chains: bool,
+    available: &[String],
+    groups: &HashMap<String, Vec<String>>,
+) -> Result<Vec<String>, Error> {
+    let unknown: Vec<UnknownChain> = requested
+        .iter()
+        .chain(excluded)
+        .filter(|entry| !groups.contains_key(*entry) && !is_glob(entry) && !available.contains(entry))
+        .map(|chain| UnknownChain { chain: chain.clone(), suggestions: suggest(chain, available) })
+        .collect();
+    if !unknown.is_empty() {
+        return Err(Error::UnknownChains(unknown));
+    }
+
+    let mut selected = if on_all_chains { available.to_vec() } else { expand(requested, available, groups) };
+
+    let excluded = expand(excluded, available, groups);
+    selected.retain(|chain| !excluded.contains(chain) && available.contains(chain));
+
+    if selected.is_empty() {
+        return Err(Error::EmptySelection);
+    }
+
+    Ok(selected)
+}
+
+// Returns up to three declared chains that are close to the provided name, closest first.
+fn suggest(
+    chain: &str,
+    available: &[String],
+) -> Vec<String> {
+    let query = chain.to_lowercase();
+    let threshold = (query.chars().count() / 3).max(2);
+
+    let mut candidates: Vec<(usize, &String)> = available
+        .iter()
+        .filter_map(|candidate| {
+            let distance = levenshtein(&query, &candidate.to_lowercase());
+            let is_close = distance <= threshold || candidate.contains(&query) || query.contains(candidate.as_str());
+            is_close.then_some((distance, candidate))
+        })
+        .collect();
+    candidates.sort();
+
+    candidates.into_iter().take(3).map(|(_, candidate)| candidate.clone()).collect()
+}
+
+// Computes the edit distance between two strings.
+fn levenshtein(
+    a: &str,
+    b: &str,
+) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// Expands groups and globs into chain names, removing duplicates while keeping the first occurrence.
+fn expand(
+    entries: &[String],
+    available: &[String],
+    groups: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    let mut chains: Vec<String> = Vec::new();
+    let mut push = |chain: &str| {
+        if !chains.iter().any(|c| c == chain) {
+            chains.push(chain.to_string());
+        }
+    };
+
+    for entry in entries {
+        let patterns = match groups.get(entry) {
+            Some(members) => members.clone(),
+            None => vec![entry.clone()],
+        };
+
+        for pattern in &patterns {
+            if is_glob(pattern) {
+                available.iter().filter(|chain| matches_glob(pattern, chain)).for_each(|chain| push(chain));
+            } else {
+                push(pattern);
+            }
+        }
+    }
+
+    chains
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains('*') || pattern.contains('?')
+}
+
+// Matches a name against a glob pattern, where `*` matches any sequence of characters and `?` a single one.
+fn matches_glob(
+    pattern: &str,
+    name: &str,
+) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in the pattern and the name position it was matched against
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` absorb one more character and retry
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, n));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn available() -> Vec<String> {
+        names(&["arbitrum", "arbitrum_sepolia", "base", "base_sepolia", "mainnet", "sepolia"])
+    }
+
+    fn groups() -> HashMap<String, Vec<String>> {
+        HashMap::from([
+            ("l2s".to_string(), names(&["arbitrum", "base", "linea"])),
+            ("testnets".to_string(), names(&["sepolia", "*_sepolia"])),
+        ])
+    }
+
+    #[test]
+    fn matches_globs_with_any_sequence_or_single_character() {
+        assert!(matches_glob("*_sepolia", "base_sepolia"));
+        assert!(!matches_glob("*_sepolia", "sepolia"));
+        assert!(matches_glob("*", "mainnet"));
+        assert!(matches_glob("ba?e", "base"));
+        assert!(!matches_glob("ba?e", "bae"));
+        assert!(matches_glob("a*_*a", "arbitrum_sepolia"));
+        assert!(!matches_glob("arbitrum", "arbitrum_sepolia"));
+    }
+
+    #[test]
+    fn expands_groups_and_globs_without_duplicates() {
+        let expanded = expand(&names(&["testnets", "base_sepolia", "l2s"]), &available(), &groups());
+
+        assert_eq!(expanded, names(&["sepolia", "arbitrum_sepolia", "base_sepolia", "arbitrum", "base", "linea"]));
+    }
+
+    #[test]
+    fn selects_groups_and_globs_in_the_requested_order() {
+        let selected = select_chains(&names(&["mainnet", "*_sepolia", "l2s"]), &[], false, &available(), &groups());
+
+        // `linea` is a member of `l2s` but is not declared, so it is skipped
+        assert_eq!(selected.unwrap(), names(&["mainnet", "arbitrum_sepolia", "base_sepolia", "arbitrum", "base"]));
+    }
+
+    #[test]
+    fn excludes_chains_groups_and_globs() {
+        let all = select_chains(&[], &names(&["testnets", "base"]), true, &available(), &groups());
+        assert_eq!(all.unwrap(), names(&["arbitrum", "mainnet"]));
+
+        let requested =
+            select_chains(&names(&["l2s", "sepolia"]), &names(&["arbitrum*"]), false, &available(), &groups());
+        assert_eq!(requested.unwrap(), names(&["base", "sepolia"]));
+    }
+
+    #[test]
+    fn computes_the_edit_distance() {
+        assert_eq!(levenshtein("arbitrum", "arbitrum"), 0);
+        assert_eq!(levenshtein("arbitrm", "arbitrum"), 1);
+        assert_eq!(levenshtein("sepolai", "sepolia"), 2);
+        assert_eq!(levenshtein("", "base"), 4);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn suggests_up_to_three_close_chains_closest_first() {
+        assert_eq!(suggest("arbitrm", &available()), names(&["arbitrum"]));
+        assert_eq!(suggest("Sepolia_Base", &available()), names(&["sepolia", "base"]));
+        assert_eq!(suggest("sepolai", &available()), names(&["sepolia"]));
+        assert_eq!(suggest("_sepolia", &available()), names(&["sepolia", "base_sepolia", "arbitrum_sepolia"]));
+        assert_eq!(suggest("polygon", &available()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejects_unknown_chains_with_suggestions() {
+        let error = select_chains(&names(&["arbitrm", "base"]), &names(&["mainet"]), false, &available(), &groups());
+
+        let Err(Error::UnknownChains(unknown)) = error else {
+            panic!("expected unknown chains, got {:?}", error);
+        };
+        let unknown: Vec<(&str, &[String])> =
+            unknown.iter().map(|chain| (chain.chain.as_str(), chain.suggestions.as_slice())).collect();
+        assert_eq!(unknown, [("arbitrm", &names(&["arbitrum"])[..]), ("mainet", &names(&["mainnet"])[..])]);
+    }
+
+    #[test]
+    fn rejects_a_selection_left_empty() {
+        let excluded = select_chains(&names(&["base"]), &names(&["l2s"]), false, &available(), &groups());
+        assert!(matches!(excluded, Err(Error::EmptySelection)));
+
+        let undeclared = select_chains(&names(&["*_holesky"]), &[], false, &available(), &groups());
+        assert!(matches!(undeclared, Err(Error::EmptySelection)));
+    }
+}
@@ -0,0 +1,117 @@
+use super::error::Error;
+use std::{fs, path::Path};
+
+// Characters that a POSIX shell reads literally outside of quotes.
+const SAFE_CHARACTERS: &str = "_-.,/:=@%+";
+
+/// Quotes a word so that a POSIX shell reads it as is. Words of safe characters are left unquoted, a reference to an
+/// environment variable such as `$PRIVATE_KEY` is double-quoted so that the shell expands it, and any other word is
+/// single-quoted.
+pub fn quote(word: &str) -> String {
+    if !word.is_empty() && word.chars().all(|c| c.is_ascii_alphanumeric() || SAFE_CHARACTERS.contains(c)) {
+        return word.to_string();
+    }
+
+    let is_variable = word.strip_prefix('$').is_some_and(|name| {
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') &&
+            name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if is_variable {
+        return format!("\"{}\"", word);
+    }
+
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Joins the words of a command, quoted for a POSIX shell.
+pub fn join(words: &[String]) -> String {
+    words.iter().map(|word| quote(word)).collect::<Vec<_>>().join(" ")
+}
+
+/// A bash script reproducing the steps of a deployment, written with `--show --emit-script` for an operator to review
+/// and run by hand. The script runs from the Foundry project, with the variables of its `.env` file, and stops at the
+/// first failing command.
+pub struct ShellScript {
+    lines: Vec<String>,
+}
+
+impl ShellScript {
+    /// Starts a script described by a comment, running from the project directory `dir`.
+    pub fn new(
+        description: &str,
+        dir: &Path,
+    ) -> Self {
+        let lines = vec![
+            "#!/usr/bin/env bash".to_string(),
+            format!("# {}", description),
+            "set -euo pipefail".to_string(),
+            String::new(),
+            "# Run from the Foundry project, with the variables of its .env file, e.g. the API keys".to_string(),
+            format!("cd {}", quote(&dir.to_string_lossy())),
+            "if [ -f .env ]; then".to_string(),
+            "  set -a".to_string(),
+            "  source .env".to_string(),
+            "  set +a".to_string(),
+            "fi".to_string(),
+        ];
+        ShellScript { lines }
+    }
+
+    /// Starts a step of the script, described by a comment.
+    pub fn step(
+        &mut self,
+        description: &str,
+    ) {
+        self.lines.push(String::new());
+        self.lines.push(format!("# {}", description));
+    }
+
+    /// Adds a comment to the current step.
+    pub fn comment(
+        &mut self,
+        comment: &str,
+    ) {
+        self.lines.push(format!("# {}", comment));
+    }
+
+    /// Adds a command line, whose words must already be quoted, e.g. by `toolchain::command_line`.
+    pub fn command(
+        &mut self,
+        line: &str,
+    ) {
+        self.lines.push(line.to_string());
+    }
+
+    /// Writes the script to `path`, and makes it executable.
+    pub fn write(
+        &self,
+        path: &Path,
+    ) -> Result<(), Error> {
+        let io_error = |source| Error::Io { path: path.to_path_buf(), source };
+        fs::write(path, format!("{}\n", self.lines.join("\n"))).map_err(io_error)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(io_error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_the_words_a_shell_would_split_or_expand() {
+        assert_eq!(quote("script/DeployFlow.s.sol"), "script/DeployFlow.s.sol");
+        assert_eq!(quote("--rpc-url"), "--rpc-url");
+        assert_eq!(quote("run(address)"), "'run(address)'");
+        assert_eq!(quote("https://explorer.mode.network/api?"), "'https://explorer.mode.network/api?'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("$PRIVATE_KEY"), "\"$PRIVATE_KEY\"");
+        assert_eq!(quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+    }
+}
@@ -0,0 +1,232 @@
+use super::{error::Error, foundry::FoundryConfig, toolchain::ToolchainRunner};
+use k256::{elliptic_curve::sec1::ToEncodedPoint, SecretKey};
+use rand::Rng;
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use std::{
+    env,
+    fs::{self, DirBuilder, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::PathBuf,
+    process,
+};
+
+// The name of the temporary keystore holding a private key read from the environment.
+const KEYSTORE_NAME: &str = "signer";
+
+// The paths shown in place of the temporary keystore, which is only created when the deployment runs.
+const SHOWN_KEYSTORE_ARGS: [&str; 4] =
+    ["--keystore", "<temporary keystore>", "--password-file", "<temporary password file>"];
+
+/// How forge signs the deployment transactions.
+#[derive(Debug, Default, PartialEq)]
+pub enum Signer {
+    /// No signer is passed, forge falls back to its own configuration.
+    #[default]
+    Default,
+    /// A keystore account managed by `cast wallet`, passed as `--account`.
+    Account(String),
+    /// A private key read from an environment variable, which is never printed.
+    PrivateKeyEnv(String),
+    /// A private key prompted by forge, passed as `--interactive`.
+    Interactive,
+}
+
+impl Signer {
+    /// Returns the forge flags selecting the signer.
+    ///
+    /// Forge doesn't read private keys from the environment, so a private key is encrypted into a temporary keystore,
+    /// which forge decrypts with a random password read from a file, and is never passed in the process arguments.
+    /// When only showing the commands, the keystore flags point to placeholders, as the keystore is only created when
+    /// the deployment runs.
+    pub fn args(
+        &self,
+        show_cli: bool,
+    ) -> Result<SignerArgs, Error> {
+        let args = match self {
+            Signer::Default => Vec::new(),
+            Signer::Account(account) => vec!["--account".to_string(), account.clone()],
+            Signer::PrivateKeyEnv(_) if show_cli => SHOWN_KEYSTORE_ARGS.map(String::from).to_vec(),
+            Signer::PrivateKeyEnv(var) => return temporary_keystore(&private_key(var)?),
+            Signer::Interactive => vec!["--interactive".to_string()],
+        };
+        Ok(SignerArgs { args, keystore_dir: None })
+    }
+
+    /// Returns the address of the signer, or `None` when it can't be known before forge runs. Keystores that don't
+    /// store their address are decrypted with `cast wallet address`, run by the given toolchain.
+    pub fn address(
+        &self,
+        runner: &dyn ToolchainRunner,
+    ) -> Result<Option<String>, Error> {
+        match self {
+            Signer::Default | Signer::Interactive => Ok(None),
+            Signer::Account(account) => keystore_address(account, runner).map(Some),
+            Signer::PrivateKeyEnv(var) => private_key_address(&private_key(var)?).map(Some),
+        }
+    }
+
+    /// Checks that the signer's address is the sender of the deployment, as forge would otherwise sign the
+    /// transactions with a different account than the one the addresses were predicted for.
+    pub fn check_sender(
+        &self,
+        sender: &str,
+        runner: &dyn ToolchainRunner,
+    ) -> Result<(), Error> {
+        match self.address(runner)? {
+            Some(address) if !address.eq_ignore_ascii_case(sender) => {
+                Err(Error::SignerMismatch { signer: address, sender: sender.to_string() })
+            }
+            Some(_) => Ok(()),
+            None => {
+                println!("The signer address can't be checked before forge runs, make sure it matches {}\n", sender);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The forge flags selecting a signer, along with the temporary keystore they may point to, which is removed when they
+/// are dropped.
+#[derive(Debug, Default)]
+pub struct SignerArgs {
+    /// The flags passed to `forge script`.
+    pub args: Vec<String>,
+    keystore_dir: Option<PathBuf>,
+}
+
+impl Drop for SignerArgs {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.keystore_dir {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+// Reads a private key from an environment variable, including the ones declared in `.env`.
+fn private_key(var: &str) -> Result<String, Error> {
+    FoundryConfig::var(var)
+        .ok_or_else(|| Error::MissingVariable { name: var.to_string(), context: "--private-key-env".to_string() })
+}
+
+// Decodes a hex private key, checking that it is a valid secp256k1 key.
+fn secret_key(private_key: &str) -> Result<SecretKey, Error> {
+    let invalid = || Error::InvalidArgument("The private key is not a valid 32-byte hex string".to_string());
+
+    let bytes = hex::decode(private_key.trim().trim_start_matches("0x")).map_err(|_| invalid())?;
+    SecretKey::from_slice(&bytes).map_err(|_| invalid())
+}
+
+// Derives the address of a private key, i.e. the last 20 bytes of the Keccak-256 hash of its public key.
+fn private_key_address(private_key: &str) -> Result<String, Error> {
+    let secret_key = secret_key(private_key)?;
+    let public_key = secret_key.public_key().to_encoded_point(false);
+
+    // Skip the leading byte tagging the key as uncompressed
+    let hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+// Encrypts a private key into a keystore, in a temporary directory only the user can access, along with the file
+// holding its random password. Returns the flags pointing forge to them.
+fn temporary_keystore(private_key: &str) -> Result<SignerArgs, Error> {
+    let secret_key = secret_key(private_key)?;
+    let dir = env::temp_dir().join(format!("deployer-keystore-{}", process::id()));
+    let io_error = |path: &PathBuf| {
+        let path = path.clone();
+        move |source| Error::Io { path, source }
+    };
+
+    let _ = fs::remove_dir_all(&dir);
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(&dir).map_err(io_error(&dir))?;
+
+    // The directory is removed once the flags are dropped, including when a step below fails
+    let mut signer_args = SignerArgs { args: Vec::new(), keystore_dir: Some(dir.clone()) };
+
+    let mut rng = rand::thread_rng();
+    let password = hex::encode(rng.gen::<[u8; 32]>());
+    eth_keystore::encrypt_key(&dir, &mut rng, secret_key.to_bytes(), &password, Some(KEYSTORE_NAME))
+        .map_err(|e| Error::Io { path: dir.join(KEYSTORE_NAME), source: io::Error::other(e.to_string()) })?;
+
+    let password_path = dir.join("password");
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&password_path)
+        .and_then(|mut file| file.write_all(password.as_bytes()))
+        .map_err(io_error(&password_path))?;
+
+    signer_args.args = vec![
+        "--keystore".to_string(),
+        dir.join(KEYSTORE_NAME).to_string_lossy().to_string(),
+        "--password-file".to_string(),
+        password_path.to_string_lossy().to_string(),
+    ];
+    Ok(signer_args)
+}
+
+// Reads the address of a keystore account. Keystores that don't store their address are decrypted by `cast`, which
+// prompts for the password.
+fn keystore_address(
+    account: &str,
+    runner: &dyn ToolchainRunner,
+) -> Result<String, Error> {
+    let path = keystores_dir().join(account);
+    let content = fs::read_to_string(&path).map_err(|source| match source.kind() {
+        ErrorKind::NotFound => {
+            Error::MissingFile { path: path.clone(), hint: "import the account with cast wallet import".to_string() }
+        }
+        _ => Error::Io { path: path.clone(), source },
+    })?;
+    let keystore: Value =
+        serde_json::from_str(&content).map_err(|e| Error::Parse { path: path.clone(), message: e.to_string() })?;
+
+    if let Some(address) = keystore.get("address").and_then(Value::as_str) {
+        return Ok(format!("0x{}", address.trim_start_matches("0x")));
+    }
+
+    let output = runner.wallet_address(account)?;
+    if !output.success {
+        return Err(Error::Keystore { account: account.to_string() });
+    }
+
+    Ok(output.stdout.trim().to_string())
+}
+
+// The directory `cast wallet import` stores keystores in.
+fn keystores_dir() -> PathBuf {
+    let home = env::var("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".foundry").join("keystores")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_placeholders_of_the_keystore_instead_of_the_private_key() {
+        let signer_args = Signer::PrivateKeyEnv("DEPLOYER_UNSET_PRIVATE_KEY".to_string()).args(true).unwrap();
+
+        assert_eq!(signer_args.args, SHOWN_KEYSTORE_ARGS);
+        assert!(signer_args.keystore_dir.is_none());
+    }
+
+    #[test]
+    fn derives_the_address_of_a_private_key() {
+        let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+        assert_eq!(private_key_address(private_key).unwrap(), "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        assert!(private_key_address("0x1234").is_err());
+    }
+}
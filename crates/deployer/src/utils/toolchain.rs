@@ -0,0 +1,252 @@
+use super::{error::Error, shell};
+use std::{
+    io::Read,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    process::{Child, Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+// How long anvil is given to fetch the fork block and start listening.
+const FORK_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The outcome of a toolchain command.
+#[derive(Clone, Debug, Default)]
+pub struct ToolOutput {
+    /// Whether the command exited successfully.
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs the Foundry commands of a deployment. The arguments follow the subcommand, e.g. the target of `forge script`,
+/// and the environment variables are set for the command only.
+///
+/// `Forge` runs the installed Foundry; tests substitute a fake that writes canned broadcast files. Runners are shared
+/// between the threads verifying contracts on several chains at once.
+pub trait ToolchainRunner: Send + Sync {
+    /// Runs `forge script`, which writes the broadcast file of the run.
+    fn run_script(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error>;
+
+    /// Runs `forge verify-contract`.
+    fn verify_contract(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error>;
+
+    /// Runs `forge build`.
+    fn build(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error>;
+
+    /// Starts a local fork of the chain behind an RPC URL, on which any sender can broadcast transactions.
+    fn start_fork(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ForkNode, Error>;
+
+    /// Runs `cast code` against an RPC URL, which prints the code deployed at an address as a hex string, `0x` when the
+    /// address has no code.
+    fn code_at(
+        &self,
+        address: &str,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error>;
+
+    /// Runs `cast chain-id` against an RPC URL, which prints the chain id of the network.
+    fn chain_id(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error>;
+
+    /// Runs `cast wallet address` for a keystore account, which prints its address once the keystore is decrypted.
+    fn wallet_address(
+        &self,
+        account: &str,
+    ) -> Result<ToolOutput, Error>;
+}
+
+/// A local fork of a chain, stopped when dropped.
+#[derive(Debug)]
+pub struct ForkNode {
+    /// The RPC URL of the fork.
+    pub url: String,
+    process: Option<Child>,
+}
+
+impl ForkNode {
+    /// Creates a fork node served by a process, which is killed when the node is dropped. Fakes pass no process.
+    pub fn new(
+        url: String,
+        process: Option<Child>,
+    ) -> Self {
+        ForkNode { url, process }
+    }
+}
+
+impl Drop for ForkNode {
+    fn drop(&mut self) {
+        if let Some(process) = &mut self.process {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+    }
+}
+
+/// The Foundry toolchain installed on the machine, run as `forge` from the `PATH`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Forge;
+
+impl Forge {
+    fn run(
+        &self,
+        subcommand: &str,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        let output = Command::new("forge")
+            .arg(subcommand)
+            .args(args)
+            .envs(env.iter().map(|(name, value)| (name, value)))
+            .output()
+            .map_err(|source| Error::Spawn { program: "forge".to_string(), source })?;
+
+        Ok(tool_output(&output))
+    }
+}
+
+// Converts the output of a process, whose streams are read as UTF-8.
+fn tool_output(output: &Output) -> ToolOutput {
+    ToolOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    }
+}
+
+impl ToolchainRunner for Forge {
+    fn run_script(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        self.run("script", args, env)
+    }
+
+    fn verify_contract(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        self.run("verify-contract", args, env)
+    }
+
+    fn build(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        self.run("build", args, env)
+    }
+
+    // Starts anvil on a free port, impersonating every sender so that the fork runs don't need the signer.
+    fn start_fork(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ForkNode, Error> {
+        let spawn_error = |source| Error::Spawn { program: "anvil".to_string(), source };
+        let port =
+            TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).and_then(|l| l.local_addr()).map_err(spawn_error)?.port();
+
+        let mut process = Command::new("anvil")
+            .args(["--fork-url", rpc_url, "--port", &port.to_string(), "--auto-impersonate", "--silent"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        // The pipe is drained for as long as anvil runs, as it would block once full, and its errors are kept in case
+        // the fork fails to start
+        let mut stderr = process.stderr.take().map(|mut pipe| {
+            thread::spawn(move || {
+                let mut output = Vec::new();
+                let _ = pipe.read_to_end(&mut output);
+                String::from_utf8_lossy(&output).into_owned()
+            })
+        });
+        let mut node = ForkNode::new(format!("http://127.0.0.1:{}", port), Some(process));
+
+        let started_at = Instant::now();
+        while TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_err() {
+            let process = node.process.as_mut().expect("the fork is served by anvil");
+            let exited = process.try_wait().map_err(spawn_error)?.is_some();
+            if exited {
+                let output = stderr.take().and_then(|reader| reader.join().ok()).unwrap_or_default();
+                return Err(Error::ForkStart { message: output.trim().to_string() });
+            }
+            if started_at.elapsed() > FORK_STARTUP_TIMEOUT {
+                return Err(Error::ForkStart { message: "it didn't start listening in time".to_string() });
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        Ok(node)
+    }
+
+    fn code_at(
+        &self,
+        address: &str,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error> {
+        let output = Command::new("cast")
+            .args(["code", address, "--rpc-url", rpc_url])
+            .output()
+            .map_err(|source| Error::Spawn { program: "cast".to_string(), source })?;
+        Ok(tool_output(&output))
+    }
+
+    fn chain_id(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error> {
+        let output = Command::new("cast")
+            .args(["chain-id", "--rpc-url", rpc_url])
+            .output()
+            .map_err(|source| Error::Spawn { program: "cast".to_string(), source })?;
+        Ok(tool_output(&output))
+    }
+
+    // The password prompt and any error are forwarded to the terminal, so the error output is left empty.
+    fn wallet_address(
+        &self,
+        account: &str,
+    ) -> Result<ToolOutput, Error> {
+        let output = Command::new("cast")
+            .args(["wallet", "address", "--account", account])
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|source| Error::Spawn { program: "cast".to_string(), source })?;
+        Ok(tool_output(&output))
+    }
+}
+
+/// Returns a `forge` command as it can be run from a shell, prefixed with its environment variables, e.g.
+/// `FOUNDRY_PROFILE=optimized forge script ...`. The words are quoted, so that the command can be pasted as is.
+pub fn command_line(
+    env: &[(String, String)],
+    subcommand: &str,
+    args: &[String],
+) -> String {
+    let mut words: Vec<String> = env.iter().map(|(name, value)| format!("{}={}", name, shell::quote(value))).collect();
+    words.push("forge".to_string());
+    words.push(subcommand.to_string());
+    words.extend(args.iter().map(|arg| shell::quote(arg)));
+    words.join(" ")
+}
@@ -0,0 +1,374 @@
+use super::{
+    broadcast::{self, BroadcastRun},
+    error::Error,
+    export::SlimBroadcast,
+    naming::ContractSettings,
+};
+use std::{collections::HashMap, fs, io::ErrorKind, path::Path};
+
+/// Where the TypeScript deployments are written when no path is provided.
+pub const DEFAULT_DEPLOYMENTS_FILE: &str = "deployments.ts";
+
+// The declaration of the array holding the deployments of every chain.
+const DECLARATION: &str = "export const chains: Sablier.Deployment[] =";
+
+/// The deployments of a chain, rendered as a `get(chains.<chain>.id, {...})` entry.
+#[derive(Debug)]
+pub struct ChainDeployment {
+    /// The name of the chain in the `chains` object of the docs, e.g. `arbitrumSepolia`.
+    pub chain: String,
+    /// The contracts, as their manifest key, address and deployment block.
+    pub contracts: Vec<(String, String, u64)>,
+}
+
+impl ChainDeployment {
+    /// Builds the entry of a chain from its broadcast. Contracts without a name or a receipt are left out, as well as
+    /// those the contract settings exclude.
+    pub fn new(
+        chain: &str,
+        broadcast: &SlimBroadcast,
+        contracts: &ContractSettings,
+    ) -> Self {
+        let contracts = broadcast
+            .contracts
+            .iter()
+            .filter_map(|contract| {
+                let name = contract.contract_name.as_deref().filter(|name| contracts.includes(chain, name))?;
+                Some((contracts.key(name), contract.contract_address.clone(), contract.block_number?))
+            })
+            .collect();
+
+        ChainDeployment { chain: ts_chain_name(chain), contracts }
+    }
+
+    // Renders the entry, with its closing line indented by `indent` and its contracts by `indent` and `step`.
+    fn render(
+        &self,
+        indent: &str,
+        step: &str,
+    ) -> String {
+        let mut lines = vec![format!("get(chains.{}.id, {{", self.chain)];
+        for (key, address, block) in &self.contracts {
+            lines.push(format!("{}{}[manifest.{}]: [\"{}\", {}],", indent, step, key, address, block));
+        }
+        lines.push(format!("{}}})", indent));
+        lines.join("\n")
+    }
+}
+
+/// Updates the `deployments.ts` file at `path` with the broadcasts of a run of a script on the given chains.
+///
+/// Only the entries of these chains are replaced, or appended when missing; the rest of the file is left untouched.
+/// The file is created when it doesn't exist.
+pub fn update_deployments_file(
+    path: &Path,
+    script_name: &str,
+    run: &BroadcastRun,
+    chains: &[String],
+    chain_ids: &HashMap<String, u64>,
+    contracts: &ContractSettings,
+) -> Result<(), Error> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(source) if source.kind() == ErrorKind::NotFound => format!("{} [];\n", DECLARATION),
+        Err(source) => return Err(Error::Io { path: path.to_path_buf(), source }),
+    };
+
+    let deployments: Vec<ChainDeployment> = broadcast::read_deployments(script_name, run, chains, chain_ids, false)?
+        .iter()
+        .map(|(chain, _, broadcast)| ChainDeployment::new(chain, broadcast, contracts))
+        .collect();
+
+    let updated = update_deployments(&source, &deployments)
+        .map_err(|message| Error::Parse { path: path.to_path_buf(), message })?;
+    broadcast::write_atomically(path, &updated)?;
+
+    println!("Updated the deployments in {}", path.display());
+    Ok(())
+}
+
+/// Replaces the entries of the given chains in the deployments array of a TypeScript source, and appends the entries
+/// of the chains that are not there yet. Everything else, including comments and formatting, is kept as-is.
+pub fn update_deployments(
+    source: &str,
+    deployments: &[ChainDeployment],
+) -> Result<String, String> {
+    let declaration = source.find(DECLARATION).ok_or_else(|| format!("missing the \"{}\" array", DECLARATION))?;
+    let after = declaration + DECLARATION.len();
+    let open = after + source[after..].len() - source[after..].trim_start().len();
+    if source.as_bytes().get(open) != Some(&b'[') {
+        return Err("the deployments must be declared as an array literal".to_string());
+    }
+    let close = matching(source, open).ok_or("the deployments array is not closed")?;
+    let entries = entries(source, open, close);
+
+    // The indentation of the entries, taken from the existing ones
+    let base_indent = line_indent(source, declaration);
+    let indent = entries.first().map_or_else(|| format!("{}  ", base_indent), |entry| line_indent(source, entry.start));
+    let step = indent.strip_prefix(base_indent.as_str()).filter(|step| !step.is_empty()).unwrap_or("  ");
+
+    // Edits are applied from the end of the source, so that their positions stay valid
+    let mut edits: Vec<(usize, usize, String)> = Vec::new();
+    let mut appended = Vec::new();
+    for deployment in deployments {
+        match entries.iter().find(|entry| entry.chain.as_deref() == Some(&deployment.chain)) {
+            Some(entry) => {
+                edits.push((entry.start, entry.end, deployment.render(&line_indent(source, entry.start), step)));
+            }
+            None => appended.push(format!("{}{},", indent, deployment.render(&indent, step))),
+        }
+    }
+
+    if !appended.is_empty() {
+        match entries.last() {
+            // After the line of the last entry, adding the comma it may lack
+            Some(last) => {
+                let after = &source[last.end..close];
+                let has_comma = after.trim_start().starts_with(',');
+                if !has_comma {
+                    edits.push((last.end, last.end, ",".to_string()));
+                }
+                let line_end = after.find('\n').map_or(close, |i| last.end + i);
+                edits.push((line_end, line_end, format!("\n{}", appended.join("\n"))));
+            }
+            // Before the closing bracket, on its own line
+            None if is_line_start(source, close) => {
+                let line_start = source[..close].rfind('\n').map_or(0, |i| i + 1);
+                edits.push((line_start, line_start, format!("{}\n", appended.join("\n"))));
+            }
+            None => edits.push((close, close, format!("\n{}\n{}", appended.join("\n"), base_indent))),
+        }
+    }
+
+    edits.sort_by_key(|(start, _, _)| *start);
+    let mut updated = source.to_string();
+    for (start, end, text) in edits.into_iter().rev() {
+        updated.replace_range(start..end, &text);
+    }
+
+    Ok(updated)
+}
+
+// Converts a chain name of `foundry.toml` to its name in the `chains` object of the docs, e.g. `arbitrum_sepolia` ->
+// `arbitrumSepolia`.
+fn ts_chain_name(chain: &str) -> String {
+    if chain == "ethereum" {
+        return "mainnet".to_string();
+    }
+
+    let mut words = chain.split(['_', '-']).filter(|word| !word.is_empty());
+    let mut name = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
+
+// A `get(chains.<chain>.id, {...})` entry of the deployments array, from `get` to the closing parenthesis.
+struct Entry {
+    start: usize,
+    end: usize,
+    chain: Option<String>,
+}
+
+// Finds the entries at the top level of the array delimited by `open` and `close`.
+fn entries(
+    source: &str,
+    open: usize,
+    close: usize,
+) -> Vec<Entry> {
+    let bytes = source.as_bytes();
+    let mut entries = Vec::new();
+
+    let mut i = open + 1;
+    while i < close {
+        if let Some(next) = skip_literal(source, i) {
+            i = next;
+        } else if source[i..].starts_with("get(") && (i == 0 || !is_identifier(bytes[i - 1])) {
+            let end = matching(source, i + 3).map_or(close, |paren| paren + 1);
+            let chain = source[i + 4..end]
+                .trim_start()
+                .strip_prefix("chains.")
+                .map(|rest| rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$').collect());
+            entries.push(Entry { start: i, end, chain });
+            i = end;
+        } else if matches!(bytes[i], b'(' | b'[' | b'{') {
+            i = matching(source, i).map_or(close, |end| end + 1);
+        } else {
+            i += 1;
+        }
+    }
+
+    entries
+}
+
+// Returns the position of the bracket closing the one at `open`, skipping strings and comments.
+fn matching(
+    source: &str,
+    open: usize,
+) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+
+    while i < bytes.len() {
+        if let Some(next) = skip_literal(source, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+// Returns the position after the string or the comment starting at `i`, if any.
+fn skip_literal(
+    source: &str,
+    i: usize,
+) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let rest = &source[i..];
+
+    if rest.starts_with("//") {
+        return Some(rest.find('\n').map_or(bytes.len(), |end| i + end));
+    }
+    if let Some(comment) = rest.strip_prefix("/*") {
+        return Some(comment.find("*/").map_or(bytes.len(), |end| i + 2 + end + 2));
+    }
+
+    let quote = bytes[i];
+    if !matches!(quote, b'"' | b'\'' | b'`') {
+        return None;
+    }
+    let mut j = i + 1;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            c if c == quote => return Some(j + 1),
+            _ => j += 1,
+        }
+    }
+    Some(bytes.len())
+}
+
+fn is_identifier(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' || byte == b'.'
+}
+
+// Returns the leading whitespace of the line containing `position`.
+fn line_indent(
+    source: &str,
+    position: usize,
+) -> String {
+    let line_start = source[..position].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect()
+}
+
+// Whether only whitespace precedes `position` on its line.
+fn is_line_start(
+    source: &str,
+    position: usize,
+) -> bool {
+    let line_start = source[..position].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..position].trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::naming::manifest_key;
+    use std::path::PathBuf;
+
+    // Compares the updated source to the expected file of a golden case. Set `UPDATE_GOLDEN=1` to rewrite the
+    // expected files instead.
+    fn check_golden(
+        case: &str,
+        deployments: &[ChainDeployment],
+    ) {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/typescript").join(case);
+        let input = fs::read_to_string(dir.join("input.ts")).unwrap();
+        let updated = update_deployments(&input, deployments).unwrap();
+
+        let expected_path = dir.join("expected.ts");
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            fs::write(&expected_path, &updated).unwrap();
+        }
+        assert_eq!(updated, fs::read_to_string(expected_path).unwrap(), "golden case {}", case);
+    }
+
+    fn deployment(
+        chain: &str,
+        contracts: &[(&str, &str, u64)],
+    ) -> ChainDeployment {
+        ChainDeployment {
+            chain: chain.to_string(),
+            contracts: contracts
+                .iter()
+                .map(|(name, address, block)| (manifest_key(name), address.to_string(), *block))
+                .collect(),
+        }
+    }
+
+    fn lockup(chain: &str) -> ChainDeployment {
+        deployment(
+            chain,
+            &[
+                ("SablierLockup", "0xcF8ce57fa442ba50aCbC57147a62aD03873FfA73", 21719029),
+                ("SablierBatchLockup", "0x3F6E8a8Cffe377c4649aCeB01e6F20c60fAA356c", 21719030),
+            ],
+        )
+    }
+
+    #[test]
+    fn inserts_into_an_empty_array() {
+        check_golden("empty", &[lockup("mainnet"), lockup("arbitrumSepolia")]);
+    }
+
+    #[test]
+    fn replaces_and_appends_entries_keeping_the_rest() {
+        check_golden("existing", &[lockup("arbitrum"), lockup("base")]);
+    }
+
+    #[test]
+    fn appends_after_an_entry_without_trailing_comma() {
+        check_golden("no_trailing_comma", &[lockup("sepolia")]);
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let input =
+            fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/typescript/existing/input.ts"))
+                .unwrap();
+        let deployments = [lockup("arbitrum"), lockup("base")];
+        let once = update_deployments(&input, &deployments).unwrap();
+        assert_eq!(update_deployments(&once, &deployments).unwrap(), once);
+    }
+
+    #[test]
+    fn rejects_a_source_without_the_array() {
+        assert!(update_deployments("export const foo = [];\n", &[lockup("mainnet")]).is_err());
+    }
+
+    #[test]
+    fn converts_chain_names() {
+        assert_eq!(ts_chain_name("arbitrum_sepolia"), "arbitrumSepolia");
+        assert_eq!(ts_chain_name("core_dao"), "coreDao");
+        assert_eq!(ts_chain_name("ethereum"), "mainnet");
+        assert_eq!(ts_chain_name("polygon"), "polygon");
+    }
+}
@@ -1,19 +1,53 @@
-use super::chain_data;
-use ethabi::{encode, Token};
+use super::{
+    batch::VerifyTarget,
+    broadcast::{BroadcastFile, BroadcastRun},
+    chain_data::ChainRegistry,
+    error::Error,
+    foundry::FoundryConfig,
+    rate_limit::RateLimiter,
+    toolchain::{self, ToolOutput, ToolchainRunner},
+};
+use ethabi::{encode, Token, Uint};
 use hex::encode as hex_encode;
+use serde::Deserialize;
 use serde_json::Value;
-use std::{env, fs, process::Command};
+use std::{collections::HashMap, thread, time::Duration};
 
-fn abi_encode(args: &[Value]) -> Result<String, String> {
-    let tokens: Result<Vec<Token>, String> = args
+/// How contracts are verified on the explorers.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct VerifySettings {
+    /// The requests allowed per second with each explorer and API key, e.g. 5 for Etherscan. 0 disables the limit.
+    pub requests_per_second: f64,
+    /// How many times a verification rate limited by the explorer is retried.
+    pub retries: u32,
+    /// The delay before the first retry, in milliseconds, doubled on each retry.
+    pub backoff_ms: u64,
+}
+
+impl Default for VerifySettings {
+    fn default() -> Self {
+        VerifySettings { requests_per_second: 5.0, retries: 5, backoff_ms: 1000 }
+    }
+}
+
+fn abi_encode(args: &[Value]) -> Result<String, Error> {
+    let tokens: Result<Vec<Token>, Error> = args
         .iter()
         .map(|arg| {
-            if let Some(addr) = arg.as_str() {
-                addr.parse().map(Token::Address).map_err(|e| format!("Failed to parse address {}: {}", addr, e))
+            // Integers that don't fit in a JSON number, e.g. token amounts, are written as strings of digits
+            if let Some(digits) = arg.as_str().filter(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())) {
+                Uint::from_dec_str(digits).map(Token::Uint).map_err(|_| {
+                    Error::InvalidArgument(format!("Unsupported integer {}, which doesn't fit in a uint256", digits))
+                })
+            } else if let Some(addr) = arg.as_str() {
+                addr.parse()
+                    .map(Token::Address)
+                    .map_err(|e| Error::InvalidArgument(format!("Failed to parse address {}: {}", addr, e)))
             } else if let Some(num) = arg.as_u64() {
                 Ok(Token::Uint(num.into()))
             } else {
-                Err("Unsupported constructor argument type".to_string())
+                Err(Error::InvalidArgument("Unsupported constructor argument type".to_string()))
             }
         })
         .collect();
@@ -23,63 +57,268 @@ fn abi_encode(args: &[Value]) -> Result<String, String> {
     Ok(format!("0x{}", hex_encode(abi_encoded_value)))
 }
 
+/// A contract created by a broadcast, as needed to verify it.
 #[derive(Debug)]
-struct VerifyData {
-    contract_name: Option<String>,
-    contract_address: Option<String>,
-    arguments: Option<Vec<Value>>,
-    libraries: Option<Vec<String>>,
+pub struct VerifyData {
+    pub contract_name: Option<String>,
+    pub contract_address: Option<String>,
+    pub arguments: Option<Vec<Value>>,
+    pub libraries: Option<Vec<String>>,
+}
+
+// The arguments and environment of `forge verify-contract` verifying a contract, along with the key of the rate limit
+// it is subject to.
+struct VerifyCommand {
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    rate_limit_key: String,
 }
 
-pub fn verify_contracts(
-    script_name: &str,
-    chains: &Vec<String>,
+/// Verifies deployed contracts on the explorers of their chains with `forge verify-contract`.
+///
+/// Requests are limited per explorer and API key, and the verifications the explorer rate limits are retried with an
+/// exponential backoff.
+pub struct Verifier<'a> {
+    runner: &'a dyn ToolchainRunner,
+    foundry: &'a FoundryConfig,
+    settings: &'a VerifySettings,
+    limiter: RateLimiter,
     show_cli: bool,
-) {
-    let mut verify_data: Vec<(String, VerifyData)> = Vec::new();
+}
+
+impl<'a> Verifier<'a> {
+    /// Creates a verifier running the given toolchain with the explorers of `foundry.toml`. When `show_cli` is set,
+    /// the commands are only printed.
+    pub fn new(
+        runner: &'a dyn ToolchainRunner,
+        foundry: &'a FoundryConfig,
+        settings: &'a VerifySettings,
+        show_cli: bool,
+    ) -> Self {
+        Verifier { runner, foundry, settings, limiter: RateLimiter::new(settings.requests_per_second), show_cli }
+    }
 
-    for chain in chains {
-        if let Err(e) = process_chain(script_name, chain, &mut verify_data, show_cli) {
-            println!("Error verifying chain {}: {}", chain, e);
+    /// Verifies the contracts created by a run of a script on the given chains. The chains are verified
+    /// concurrently, one contract at a time on each chain. Errors are reported for each chain and contract, so that
+    /// the other contracts are still verified, and what failed is returned, see `verify_contracts`.
+    pub fn verify_all(
+        &self,
+        script_name: &str,
+        run: &BroadcastRun,
+        chains: &[String],
+        chain_ids: &HashMap<String, u64>,
+    ) -> Vec<String> {
+        let mut verify_data: Vec<(String, Vec<VerifyData>)> = Vec::new();
+        let mut failed = Vec::new();
+
+        for chain in chains {
+            match self.contracts(script_name, run, chain, chain_ids[chain]) {
+                Ok(contracts) => verify_data.push((chain.clone(), contracts)),
+                Err(e) => {
+                    println!("For chain {}, the contracts can't be verified: {}", chain, e);
+                    failed.push(format!("the contracts on {}", chain));
+                }
+            }
         }
+
+        failed.extend(self.verify_contracts(&verify_data, chain_ids));
+        failed
     }
 
-    // Set environment variable for Foundry profile once
-    env::set_var("FOUNDRY_PROFILE", "optimized");
+    /// Verifies contracts deployed without a broadcast, e.g. by hand or by another tool. Returns an error listing the
+    /// contracts that couldn't be verified.
+    pub fn verify_targets(
+        &self,
+        targets: &[VerifyTarget],
+        chain_ids: &HashMap<String, u64>,
+    ) -> Result<(), Error> {
+        let mut contracts: Vec<(String, Vec<VerifyData>)> = Vec::new();
+        for target in targets {
+            match contracts.iter_mut().find(|(chain, _)| *chain == target.chain) {
+                Some((_, chain_contracts)) => chain_contracts.push(target.verify_data()),
+                None => contracts.push((target.chain.clone(), vec![target.verify_data()])),
+            }
+        }
 
-    // Iterate over all transactions and verify each contract.
-    for (chain, data) in verify_data {
-        let contract_name = match &data.contract_name {
-            Some(name) => name,
-            None => continue,
-        };
+        let failed = self.verify_contracts(&contracts, chain_ids);
+        if !failed.is_empty() {
+            return Err(Error::FailedVerifications(failed));
+        }
+        Ok(())
+    }
 
-        let contract_addr = match &data.contract_address {
-            Some(addr) => addr,
-            None => continue,
-        };
+    /// Verifies the given contracts of each chain. The chains are verified concurrently, one contract at a time on
+    /// each chain. Errors are reported for each contract, and the contracts that failed are returned as
+    /// `<name> on <chain>`.
+    pub fn verify_contracts(
+        &self,
+        contracts: &[(String, Vec<VerifyData>)],
+        chain_ids: &HashMap<String, u64>,
+    ) -> Vec<String> {
+        // The commands are printed in order when only showing them
+        if self.show_cli {
+            return contracts
+                .iter()
+                .flat_map(|(chain, contracts)| self.verify_chain(chain, chain_ids[chain], contracts))
+                .collect();
+        }
 
-        let constructor_args = if let Some(args) = &data.arguments {
-            if !args.is_empty() {
-                match abi_encode(args) {
-                    Ok(encoded) => encoded,
-                    Err(e) => {
-                        println!("For chain {}, error encoding arguments for {}: {}", chain, contract_name, e);
-                        continue;
+        thread::scope(|scope| {
+            let handles: Vec<_> = contracts
+                .iter()
+                .map(|(chain, contracts)| {
+                    let chain_id = chain_ids[chain];
+                    (chain, scope.spawn(move || self.verify_chain(chain, chain_id, contracts)))
+                })
+                .collect();
+
+            // A chain whose verification panicked has none of its contracts verified
+            handles
+                .into_iter()
+                .flat_map(|(chain, handle)| {
+                    handle.join().unwrap_or_else(|_| {
+                        println!("For chain {}, the verification stopped unexpectedly", chain);
+                        vec![format!("the contracts on {}", chain)]
+                    })
+                })
+                .collect()
+        })
+    }
+
+    // Verifies the contracts of a chain one after the other, reporting the outcome of each. Returns the contracts that
+    // failed.
+    fn verify_chain(
+        &self,
+        chain: &str,
+        chain_id: u64,
+        contracts: &[VerifyData],
+    ) -> Vec<String> {
+        let mut failed = Vec::new();
+        for data in contracts {
+            let Some(contract_name) = &data.contract_name else {
+                continue;
+            };
+            if data.contract_address.is_none() {
+                continue;
+            }
+
+            match self.verify(chain, chain_id, data) {
+                Ok(Some(output)) => println!("Successfully verified {} on {}: {}", contract_name, chain, output),
+                Ok(None) => {}
+                Err(e) => {
+                    match e {
+                        Error::Forge { .. } => println!("Failed to verify {}: {}", contract_name, e),
+                        _ => println!("For chain {}, the contract {} can't be verified: {}", chain, contract_name, e),
                     }
+                    failed.push(format!("{} on {}", contract_name, chain));
                 }
-            } else {
-                String::new()
             }
-        } else {
-            String::new()
+        }
+        failed
+    }
+
+    /// Returns the contracts created by a run of a script on a chain.
+    pub fn contracts(
+        &self,
+        script_name: &str,
+        run: &BroadcastRun,
+        chain: &str,
+        chain_id: u64,
+    ) -> Result<Vec<VerifyData>, Error> {
+        let (_, json_value) = BroadcastFile::new(script_name, chain, chain_id, true).with_run(run).read()?;
+
+        // Extract libraries from the JSON
+        let libraries: Option<Vec<String>> = json_value
+            .get("libraries")
+            .and_then(|v| v.as_array())
+            .map(|lib_array| lib_array.iter().filter_map(|lib| lib.as_str().map(|s| s.to_string())).collect());
+
+        let mut contracts = Vec::new();
+        if let Some(tx_array) = json_value.get("transactions").and_then(|v| v.as_array()) {
+            for tx_value in tx_array {
+                let contract_name = tx_value.get("contractName").and_then(|v| v.as_str()).map(String::from);
+                let contract_address = tx_value.get("contractAddress").and_then(|v| v.as_str()).map(String::from);
+
+                let arguments = match tx_value.get("arguments") {
+                    Some(Value::Array(args)) if !args.is_empty() => Some(args.clone()),
+                    _ => None,
+                };
+
+                contracts.push(VerifyData { contract_name, contract_address, arguments, libraries: libraries.clone() });
+            }
+        }
+
+        Ok(contracts)
+    }
+
+    /// Verifies a contract on the explorer of a chain, returning the output of forge. When only showing the commands,
+    /// the command is printed and `None` is returned. The verification waits for the rate limit of the explorer, and is
+    /// retried when the explorer rate limits it anyway.
+    pub fn verify(
+        &self,
+        chain: &str,
+        chain_id: u64,
+        data: &VerifyData,
+    ) -> Result<Option<String>, Error> {
+        let Some(contract_name) = &data.contract_name else {
+            return Err(Error::InvalidArgument("The contract name and address are required".to_string()));
         };
+        let VerifyCommand { args: args_vec, env, rate_limit_key } = self.command(chain, chain_id, data)?;
+        let full_command = toolchain::command_line(&env, "verify-contract", &args_vec);
+
+        if self.show_cli {
+            println!("Verification command to be executed: {} \n", full_command);
+            return Ok(None);
+        }
 
-        let mut args_vec = vec![
-            "verify-contract".to_string(),
-            contract_addr.to_string(),
-            format!("src/{}.sol:{}", contract_name, contract_name),
-        ];
+        let mut retries = 0;
+        loop {
+            self.limiter.acquire(&rate_limit_key);
+            let output = self.runner.verify_contract(&args_vec, &env)?;
+            if output.success {
+                return Ok(Some(output.stdout));
+            }
+
+            if !is_rate_limited(&output) || retries == self.settings.retries {
+                return Err(Error::Forge {
+                    chain: chain.to_string(),
+                    stderr: format!("{}\nThe command was: {}", output.stderr.trim_end(), full_command),
+                });
+            }
+
+            let backoff = Duration::from_millis(self.settings.backoff_ms.saturating_mul(1 << retries.min(16)));
+            println!(
+                "The explorer of {} rate limited the verification of {}, retrying in {:?}",
+                chain, contract_name, backoff
+            );
+            thread::sleep(backoff);
+            retries += 1;
+        }
+    }
+
+    // Builds the `forge verify-contract` command verifying a contract.
+    fn command(
+        &self,
+        chain: &str,
+        chain_id: u64,
+        data: &VerifyData,
+    ) -> Result<VerifyCommand, Error> {
+        let (Some(contract_name), Some(contract_addr)) = (&data.contract_name, &data.contract_address) else {
+            return Err(Error::InvalidArgument("The contract name and address are required".to_string()));
+        };
+
+        let constructor_args = match &data.arguments {
+            Some(args) if !args.is_empty() => abi_encode(args)?,
+            _ => String::new(),
+        };
+
+        // Contracts are looked up in `src` by their name, unless given with their path as `<path>:<name>`
+        let contract_id = if contract_name.contains(':') {
+            contract_name.clone()
+        } else {
+            format!("src/{}.sol:{}", contract_name, contract_name)
+        };
+        let mut args_vec = vec![contract_addr.to_string(), contract_id];
 
         if !constructor_args.is_empty() {
             args_vec.push("--constructor-args".to_string());
@@ -96,101 +335,122 @@ pub fn verify_contracts(
             }
         }
 
-        let mut verifier_flags = get_verifier_flags(&chain);
+        let (mut verifier_flags, api_key) = get_verifier_flags(chain, chain_id, self.foundry, self.show_cli)?;
         args_vec.append(&mut verifier_flags);
 
-        let full_command = format!("forge {}", args_vec.join(" "));
-
-        if show_cli {
-            println!("Verification command to be executed: FOUNDRY_PROFILE=optimized {} \n", full_command);
-        } else {
-            match Command::new("forge").args(&args_vec).output() {
-                Ok(output) => {
-                    if !output.status.success() {
-                        println!(
-                            "For chain {}, the verification did not work for contract {} using the command: \
-                             {}\nError: {}",
-                            chain,
-                            contract_name,
-                            full_command,
-                            String::from_utf8_lossy(&output.stderr)
-                        );
-                    } else {
-                        println!(
-                            "Successfully verified {}: {}",
-                            contract_name,
-                            String::from_utf8_lossy(&output.stdout)
-                        );
-                    }
-                }
-                Err(e) => {
-                    println!("For chain {}, failed to run forge verify-contract command: {}", chain, e);
-                }
-            }
+        // The API key is passed through the environment of forge, so that it doesn't show in the process arguments
+        let mut env = self.foundry.chain_env(chain_id);
+        if !api_key.is_empty() {
+            env.push(("ETHERSCAN_API_KEY".to_string(), api_key.clone()));
         }
+
+        // Requests are limited per explorer and API key, chains without a verifier URL being verified on Etherscan
+        let verifier_url = args_vec.iter().position(|arg| arg == "--verifier-url").map(|i| args_vec[i + 1].as_str());
+        let rate_limit_key = format!("{} {}", verifier_url.unwrap_or("etherscan"), api_key);
+
+        Ok(VerifyCommand { args: args_vec, env, rate_limit_key })
     }
 }
 
-fn process_chain(
-    script_name: &str,
-    chain: &str,
-    verify_data: &mut Vec<(String, VerifyData)>,
-    show_cli: bool,
-) -> Result<(), String> {
-    let chain_id = chain_data::get_chain_id(chain);
-    let file_path: String = if show_cli {
-        format!("broadcast/{}/{}/dry-run/run-latest.json", script_name, chain_id)
-    } else {
-        format!("broadcast/{}/{}/run-latest.json", script_name, chain_id)
-    };
-
-    let json_content =
-        fs::read_to_string(&file_path).map_err(|_| format!("Failed to read the broadcast file: {}", &file_path))?;
-    let json_value: Value =
-        serde_json::from_str(&json_content).map_err(|_| format!("Failed to parse JSON in file: {}", &file_path))?;
-
-    // Extract libraries from the JSON
-    let libraries: Option<Vec<String>> = json_value
-        .get("libraries")
-        .and_then(|v| v.as_array())
-        .map(|lib_array| lib_array.iter().filter_map(|lib| lib.as_str().map(|s| s.to_string())).collect());
-
-    if let Some(tx_array) = json_value.get("transactions").and_then(|v| v.as_array()) {
-        for tx_value in tx_array {
-            let contract_name = tx_value.get("contractName").and_then(|v| v.as_str()).map(String::from);
-            let contract_address = tx_value.get("contractAddress").and_then(|v| v.as_str()).map(String::from);
-
-            let arguments = match tx_value.get("arguments") {
-                Some(Value::Array(args)) if !args.is_empty() => Some(args.clone()),
-                _ => None,
-            };
+// Tells whether forge failed because the explorer rate limited the request, e.g. with Etherscan's "Max calls per sec
+// rate limit reached".
+fn is_rate_limited(output: &ToolOutput) -> bool {
+    [&output.stderr, &output.stdout].iter().any(|text| {
+        let text = text.to_lowercase();
+        text.contains("rate limit") || text.contains("too many requests")
+    })
+}
 
-            verify_data.push((
-                chain.to_string(),
-                VerifyData { contract_name, contract_address, arguments, libraries: libraries.clone() },
-            ));
-        }
+// Returns an `[etherscan]` key as shown in the commands, which is the reference to its variable when it is one, e.g.
+// `${SEPOLIA_API_KEY}`. Literal keys are redacted to the `<CHAIN>_API_KEY` variable, to be set when running the
+// commands.
+fn shown_key(
+    key: &str,
+    chain: &str,
+) -> String {
+    let variable = key.strip_prefix("${").and_then(|rest| rest.strip_suffix('}')).filter(|name| {
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') &&
+            name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    match variable {
+        Some(name) => format!("${}", name),
+        None => format!("${}_API_KEY", chain.to_uppercase().replace('-', "_")),
     }
-
-    Ok(())
 }
 
-fn get_verifier_flags(chain: &str) -> Vec<String> {
+// Builds the explorer flags and the API key for a chain. The `[etherscan]` entry of the chain in `foundry.toml` is
+// used when there is one, otherwise the flags are derived from the chain name. zkEVM chains are verified in zkSync
+// mode, with their zkSync verifier unless an `[etherscan]` entry is declared, which needs no API key. When only
+// showing the command, API keys are printed as references to the environment instead of their values, see
+// `shown_key`.
+fn get_verifier_flags(
+    chain: &str,
+    chain_id: u64,
+    foundry: &FoundryConfig,
+    show_cli: bool,
+) -> Result<(Vec<String>, String), Error> {
     let mut args = vec!["--verify".to_string()];
-    args.push("--etherscan-api-key".to_string());
+    let zk_verifier_url = ChainRegistry::zk_verifier_url(chain_id);
+    if zk_verifier_url.is_some() {
+        args.push("--zksync".to_string());
+    }
 
-    if chain.eq("form") || chain.eq("lightlink") || chain.eq("mode") || chain.eq("morph") || chain.eq("superseed") {
-        args.push("\"verifyContract\"".to_string());
-        let explorer_url = chain_data::get_explorer_url_by_name(chain);
+    let api_key = if let Some(etherscan) = foundry.etherscan(chain, chain_id) {
+        if let Some(url) = &etherscan.url {
+            args.push("--verifier-url".to_string());
+            args.push(foundry.expand(url, &format!("The etherscan URL of chain {}", chain))?);
+        }
+        if show_cli {
+            shown_key(&etherscan.key, chain)
+        } else {
+            foundry.expand(&etherscan.key, &format!("The etherscan key of chain {}", chain))?
+        }
+    } else if let Some(url) = zk_verifier_url {
+        args.extend(["--verifier".to_string(), "zksync".to_string(), "--verifier-url".to_string(), url.to_string()]);
+        String::new()
+    } else if chain.eq("form") ||
+        chain.eq("lightlink") ||
+        chain.eq("mode") ||
+        chain.eq("morph") ||
+        chain.eq("superseed")
+    {
+        let explorer_url = ChainRegistry::explorer_url_by_name(chain);
         args.push("--verifier-url".to_string());
-        args.push(format!("{}api\\?", explorer_url));
+        args.push(format!("{}api?", explorer_url));
+        "verifyContract".to_string()
     } else if chain.eq("chiliz") {
-        args.push("\"verifyContract\"".to_string());
         args.push("--verifier-url".to_string());
         args.push("https://api.routescan.io/v2/network/mainnet/evm/88888/etherscan".to_string());
+        "verifyContract".to_string()
     } else {
-        args.push(format!("${}_API_KEY", chain.to_uppercase()));
-    }
+        let key_var = format!("{}_API_KEY", chain.to_uppercase());
+        if show_cli {
+            format!("${}", key_var)
+        } else {
+            FoundryConfig::var(&key_var).ok_or_else(|| Error::MissingVariable {
+                name: key_var.clone(),
+                context: format!("The verification on chain {}", chain),
+            })?
+        }
+    };
 
-    args
+    Ok((args, api_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_integers_larger_than_u64() {
+        let amount = "1000000000000000000000000";
+        let encoded = abi_encode(&[Value::from(amount), Value::from(5u64)]).unwrap();
+        assert_eq!(encoded, format!("0x{:0>64}{:0>64}", "d3c21bcecceda1000000", "5"));
+
+        let too_large = "1".repeat(80);
+        match abi_encode(&[Value::from(too_large.as_str())]) {
+            Err(Error::InvalidArgument(message)) => assert!(message.contains("uint256"), "{}", message),
+            other => panic!("expected the integer to be rejected, got {:?}", other),
+        }
+    }
 }
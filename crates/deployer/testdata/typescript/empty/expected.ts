@@ -0,0 +1,14 @@
+import { chains } from "@src/chains";
+import manifest from "@src/evm/releases/lockup/v2.0/manifest";
+import type { Sablier } from "@src/types";
+
+export const chains: Sablier.Deployment[] = [
+  get(chains.mainnet.id, {
+    [manifest.SABLIER_LOCKUP]: ["0xcF8ce57fa442ba50aCbC57147a62aD03873FfA73", 21719029],
+    [manifest.SABLIER_BATCH_LOCKUP]: ["0x3F6E8a8Cffe377c4649aCeB01e6F20c60fAA356c", 21719030],
+  }),
+  get(chains.arbitrumSepolia.id, {
+    [manifest.SABLIER_LOCKUP]: ["0xcF8ce57fa442ba50aCbC57147a62aD03873FfA73", 21719029],
+    [manifest.SABLIER_BATCH_LOCKUP]: ["0x3F6E8a8Cffe377c4649aCeB01e6F20c60fAA356c", 21719030],
+  }),
+];
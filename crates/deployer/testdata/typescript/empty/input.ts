@@ -0,0 +1,5 @@
+import { chains } from "@src/chains";
+import manifest from "@src/evm/releases/lockup/v2.0/manifest";
+import type { Sablier } from "@src/types";
+
+export const chains: Sablier.Deployment[] = [];
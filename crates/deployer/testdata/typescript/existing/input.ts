@@ -0,0 +1,21 @@
+import { chains } from "@src/chains";
+import manifest from "@src/evm/releases/lockup/v2.0/manifest";
+import type { Sablier } from "@src/types";
+
+/**
+ * Deployments of Lockup v2.0, kept in the order of the release notes.
+ */
+export const chains: Sablier.Deployment[] = [
+  get(chains.mainnet.id, {
+    [manifest.SABLIER_LOCKUP]: ["0x7C01AA3783577E15fD7e272443D44B92d5b21056", 21719029],
+    // Redeployed after the audit fix
+    [manifest.SABLIER_BATCH_LOCKUP]: ["0x3F6E8a8Cffe377c4649aCeB01e6F20c60fAA356c", 21719030],
+  }),
+  // Arbitrum was deployed by the multisig, see the release notes
+  get(chains.arbitrum.id, {
+    [manifest.SABLIER_LOCKUP]: ["0x0000000000000000000000000000000000000001", 1],
+  }), // to be redeployed
+  get(chains.avalanche.id, { [manifest.SABLIER_LOCKUP]: ["0x3C81BBBe72EF8eF3fb1D19B0bd6310Ad0dd27E82", 56642237] }),
+];
+
+export const mainnets = chains.filter((deployment) => !deployment.isTestnet);
@@ -0,0 +1,9 @@
+export const chains: Sablier.Deployment[] = [
+    get(chains.mainnet.id, {
+        [manifest.SABLIER_LOCKUP]: ["0x7C01AA3783577E15fD7e272443D44B92d5b21056", 21719029],
+    }),
+    get(chains.sepolia.id, {
+        [manifest.SABLIER_LOCKUP]: ["0xcF8ce57fa442ba50aCbC57147a62aD03873FfA73", 21719029],
+        [manifest.SABLIER_BATCH_LOCKUP]: ["0x3F6E8a8Cffe377c4649aCeB01e6F20c60fAA356c", 21719030],
+    }),
+];
@@ -0,0 +1,5 @@
+export const chains: Sablier.Deployment[] = [
+    get(chains.mainnet.id, {
+        [manifest.SABLIER_LOCKUP]: ["0x7C01AA3783577E15fD7e272443D44B92d5b21056", 21719029],
+    })
+];
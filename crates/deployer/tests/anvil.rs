@@ -0,0 +1,233 @@
+//! Tests running the installed anvil and cast on a local chain, without any external network. They are skipped when
+//! Foundry is not installed.
+
+mod common;
+
+use common::{Project, BATCH_LOCKUP, LOCKUP};
+use deployer::{
+    utils::{forge::ScriptOptions, toolchain::ForkNode},
+    Config, DeployOptions, Deployer, Error, Forge, FoundryConfig, ToolOutput, ToolchainRunner,
+};
+use std::{
+    fs,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+const ADDRESS: &str = "0x00000000000000000000000000000000000c0de5";
+const SCRIPT: &str = "DeployProtocol.s.sol";
+// The chain id of the local chains, which their forks keep
+const ANVIL_CHAIN_ID: u64 = 31337;
+
+// A local chain served by anvil, stopped when dropped.
+struct Anvil {
+    url: String,
+    process: Child,
+}
+
+impl Anvil {
+    fn start() -> Self {
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap().port();
+        let process = Command::new("anvil")
+            .args(["--port", &port.to_string(), "--silent"])
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        while TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_err() {
+            thread::sleep(Duration::from_millis(100));
+        }
+        Anvil { url: format!("http://127.0.0.1:{}", port), process }
+    }
+}
+
+impl Drop for Anvil {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Runs Foundry, except for the scripts, which deploy `SablierLockup` and `SablierBatchLockup` by setting their code
+/// on the node with `cast` and then write the broadcast forge would. Nothing is compiled, so solc isn't needed.
+#[derive(Default)]
+struct AnvilScripts {
+    // Addresses at which the scripts deploy no code, as when a deployment silently fails
+    missing_code: Vec<&'static str>,
+}
+
+impl ToolchainRunner for AnvilScripts {
+    fn run_script(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        let rpc_url = args.iter().position(|arg| arg == "--rpc-url").map(|i| args[i + 1].clone()).unwrap();
+        let broadcast_dir = env.iter().find(|(key, _)| key == "FOUNDRY_BROADCAST").map_or("broadcast", |(_, v)| v);
+        let chain_id: u64 = Forge.chain_id(&rpc_url)?.stdout.trim().parse().unwrap();
+
+        let mut dir = Path::new(broadcast_dir).join(SCRIPT).join(chain_id.to_string());
+        if args.iter().any(|arg| arg == "--broadcast") {
+            for address in [LOCKUP, BATCH_LOCKUP].into_iter().filter(|address| !self.missing_code.contains(address)) {
+                set_code(address, &rpc_url);
+            }
+        } else {
+            dir = dir.join("dry-run");
+        }
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("run-latest.json"), common::broadcast(chain_id)).unwrap();
+
+        Ok(ToolOutput { success: true, ..ToolOutput::default() })
+    }
+
+    fn verify_contract(
+        &self,
+        _args: &[String],
+        _env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        unimplemented!("the anvil tests don't verify contracts")
+    }
+
+    fn build(
+        &self,
+        _args: &[String],
+        _env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        Ok(ToolOutput { success: true, ..ToolOutput::default() })
+    }
+
+    fn start_fork(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ForkNode, Error> {
+        Forge.start_fork(rpc_url)
+    }
+
+    fn code_at(
+        &self,
+        address: &str,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error> {
+        Forge.code_at(address, rpc_url)
+    }
+
+    fn chain_id(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error> {
+        Forge.chain_id(rpc_url)
+    }
+
+    fn wallet_address(
+        &self,
+        account: &str,
+    ) -> Result<ToolOutput, Error> {
+        Forge.wallet_address(account)
+    }
+}
+
+// Deploys code at an address of an anvil node.
+fn set_code(
+    address: &str,
+    rpc_url: &str,
+) {
+    let status = Command::new("cast")
+        .args(["rpc", "anvil_setCode", address, "0x6080", "--rpc-url", rpc_url])
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn is_installed(program: &str) -> bool {
+    Command::new(program).arg("--version").output().is_ok_and(|output| output.status.success())
+}
+
+fn skip_without_foundry() -> bool {
+    let missing = !is_installed("anvil") || !is_installed("cast");
+    if missing {
+        eprintln!("Skipping the anvil tests, as anvil or cast is not installed");
+    }
+    missing
+}
+
+// Points the `devnet` RPC endpoint of the project to a local chain, and checks the deployment on a fork of it.
+fn fork_check_on(
+    chain: &Anvil,
+    runner: AnvilScripts,
+) -> Result<(), Error> {
+    let foundry_toml = fs::read_to_string("foundry.toml").unwrap().replace("http://localhost:8545/devnet", &chain.url);
+    fs::write("foundry.toml", foundry_toml).unwrap();
+
+    let foundry = FoundryConfig::load("default").unwrap();
+    let script = ScriptOptions { name: SCRIPT.to_string(), ..ScriptOptions::default() };
+    let options = DeployOptions { broadcast: true, fork_check: true, ..DeployOptions::default() };
+    Deployer::new(Config::default(), foundry, script, options).with_runner(runner).deploy(&["devnet".to_string()])
+}
+
+#[test]
+fn forks_keep_the_code_of_the_forked_chain() {
+    if skip_without_foundry() {
+        return;
+    }
+
+    let chain = Anvil::start();
+    set_code(ADDRESS, &chain.url);
+
+    let fork = Forge.start_fork(&chain.url).unwrap();
+    assert_ne!(fork.url, chain.url);
+    assert_eq!(Forge.code_at(ADDRESS, &fork.url).unwrap().stdout.trim(), "0x6080");
+    assert_eq!(Forge.code_at("0x0000000000000000000000000000000000000001", &fork.url).unwrap().stdout.trim(), "0x");
+}
+
+#[test]
+fn checks_the_deployment_on_a_fork_before_broadcasting() {
+    if skip_without_foundry() {
+        return;
+    }
+    let project = Project::new("anvil-fork-check");
+    let chain = Anvil::start();
+
+    fork_check_on(&chain, AnvilScripts::default()).unwrap();
+
+    // The fork check ran on its own broadcast directory, then the deployment went through on the chain
+    let fork_broadcast = format!("cache/fork-check/{}/{}/run-latest.json", SCRIPT, ANVIL_CHAIN_ID);
+    assert!(project.dir.join(fork_broadcast).exists());
+    assert!(project.dir.join(format!("broadcast/{}/{}/run-latest.json", SCRIPT, ANVIL_CHAIN_ID)).exists());
+    assert_eq!(Forge.code_at(BATCH_LOCKUP, &chain.url).unwrap().stdout.trim(), "0x6080");
+}
+
+#[test]
+fn broadcasts_nothing_when_the_fork_lacks_a_contract() {
+    if skip_without_foundry() {
+        return;
+    }
+    let project = Project::new("anvil-fork-check-failure");
+    let chain = Anvil::start();
+
+    let result = fork_check_on(&chain, AnvilScripts { missing_code: vec![BATCH_LOCKUP] });
+
+    match result {
+        Err(Error::ForkCheckFailed(failed)) => assert_eq!(failed, ["devnet"]),
+        other => panic!("expected the fork check to fail, got {:?}", other),
+    }
+    assert!(!project.dir.join(format!("broadcast/{}/{}/run-latest.json", SCRIPT, ANVIL_CHAIN_ID)).exists());
+    // The fork had the code of the other contract, but the chain was left untouched
+    assert_eq!(Forge.code_at(LOCKUP, &chain.url).unwrap().stdout.trim(), "0x");
+}
+
+#[test]
+fn reports_why_a_fork_failed_to_start() {
+    if skip_without_foundry() {
+        return;
+    }
+    // Nothing listens on a port once its listener is dropped
+    let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap().port();
+
+    match Forge.start_fork(&format!("http://127.0.0.1:{}", port)) {
+        Err(Error::ForkStart { message }) => assert!(!message.is_empty()),
+        other => panic!("expected the fork to fail to start, got {:?}", other),
+    }
+}
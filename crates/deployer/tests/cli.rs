@@ -0,0 +1,47 @@
+//! Tests running the deployer binary, for the flags that are parsed before any command runs.
+
+mod common;
+
+use common::{Project, ADMIN, LOCKUP};
+use std::process::{Command, Output};
+
+fn deployer(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_deployer")).args(args).output().unwrap()
+}
+
+#[test]
+fn verifies_the_contract_given_with_verify_contract() {
+    let _project = Project::new("cli-verify-contract");
+
+    let output = deployer(&[
+        "verify",
+        "--chain",
+        "sepolia",
+        "--address",
+        LOCKUP,
+        "--verify-contract",
+        "SablierLockup",
+        "--args",
+        ADMIN,
+        "--show",
+    ]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains(&format!("{} src/SablierLockup.sol:SablierLockup", LOCKUP)), "{}", stdout);
+}
+
+#[test]
+fn keeps_the_contract_of_the_script_apart_from_the_contract_to_verify() {
+    let _project = Project::new("cli-contract");
+
+    // --contract selects the contract of a script, so it doesn't stand for the contract to verify
+    let output = deployer(&["verify", "--chain", "sepolia", "--address", LOCKUP, "--contract", "SablierLockup"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--verify-contract"));
+
+    // Nor is --verify-contract taken for the contract of a script
+    let output = deployer(&["sepolia", "--script", "DeployProtocol.s.sol", "--verify-contract", "DeployProtocol"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--verify-contract is only used by the verify command"));
+}
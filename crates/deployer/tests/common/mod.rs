@@ -0,0 +1,408 @@
+//! A fake Foundry toolchain and a throwaway Foundry project, for testing deployments offline.
+
+// Each test file uses its own part of the helpers
+#![allow(dead_code)]
+
+use deployer::{utils::toolchain::ForkNode, ChainRegistry, Error, ToolOutput, ToolchainRunner};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+// The deployer reads and writes files relative to the current directory, so tests changing it run one at a time.
+static CURRENT_DIR: Mutex<()> = Mutex::new(());
+
+pub const ADMIN: &str = "0xb1bef51ebca01eb12001a639bdbbff6eeca12b9f";
+pub const LOCKUP: &str = "0xcf8ce57fa442ba50acbc57147a62ad03873ffa73";
+pub const BATCH_LOCKUP: &str = "0x0636d83b184d65c242c43de6aad10535bfb9d45a";
+
+const FOUNDRY_TOML: &str = r#"[profile.default]
+src = "src"
+
+[rpc_endpoints]
+arbitrum = "http://localhost:8545/arbitrum"
+chiliz = "http://localhost:8545/chiliz"
+devnet = "http://localhost:8545/devnet"
+sepolia = "http://localhost:8545/sepolia"
+zksync = "http://localhost:8545/zksync"
+
+[etherscan]
+arbitrum = { key = "arbitrum-key" }
+chiliz = { key = "chiliz-key" }
+sepolia = { key = "sepolia-key" }
+"#;
+
+const PACKAGE_JSON: &str = r#"{ "name": "@sablier/lockup", "version": "2.0.0" }"#;
+
+/// A Foundry project in a temporary directory, which is the current directory until it is dropped.
+pub struct Project {
+    pub dir: PathBuf,
+    previous_dir: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Project {
+    pub fn new(name: &str) -> Self {
+        let guard = CURRENT_DIR.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let dir = env::temp_dir().join(format!("deployer-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("foundry.toml"), FOUNDRY_TOML).unwrap();
+        fs::write(dir.join("package.json"), PACKAGE_JSON).unwrap();
+
+        let previous_dir = env::current_dir().unwrap();
+        env::set_current_dir(&dir).unwrap();
+        Project { dir, previous_dir, _guard: guard }
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.previous_dir);
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A command run by the fake toolchain.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+impl Call {
+    fn new(
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Self {
+        Call { args: args.to_vec(), env: env.iter().cloned().collect() }
+    }
+
+    /// Returns the value following a flag.
+    pub fn flag(
+        &self,
+        flag: &str,
+    ) -> Option<String> {
+        self.args.iter().position(|arg| arg == flag).and_then(|i| self.args.get(i + 1)).cloned()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    builds: Vec<Call>,
+    failing_build: bool,
+    scripts: Vec<Call>,
+    verifications: Vec<Call>,
+    failing_chains: Vec<String>,
+    chain_ids: HashMap<String, u64>,
+    // The forks started, by URL, along with the chain they fork
+    forks: HashMap<String, String>,
+    // The addresses with code on each chain or fork, keyed by chain name or fork URL
+    code: HashMap<String, Vec<String>>,
+    // Addresses at which the scripts deploy no code, as when a deployment silently fails
+    missing_code: Vec<String>,
+    // How many verifications are still to be rate limited by the explorers
+    rate_limited: usize,
+    // The API keys whose verifications panic, as when the toolchain has a bug
+    panicking_keys: Vec<String>,
+    // The private keys decrypted from the keystores passed to the scripts
+    keystore_keys: Vec<String>,
+    // The addresses of the keystore accounts, as decrypted by `cast wallet address`
+    accounts: HashMap<String, String>,
+}
+
+impl State {
+    // Resolves an RPC URL, a chain name or a fork URL, into the network it points to and its chain.
+    fn network(
+        &self,
+        rpc_url: &str,
+    ) -> (String, String) {
+        if let Some(chain) = self.forks.get(rpc_url) {
+            return (rpc_url.to_string(), chain.clone());
+        }
+        let chain = rpc_url.rsplit('/').next().unwrap().to_string();
+        (chain.clone(), chain)
+    }
+}
+
+/// A toolchain that records its calls instead of running forge. Scripts write a canned broadcast deploying
+/// `SablierLockup` and `SablierBatchLockup`, and verifications succeed unless rate limited. Clones share their state,
+/// so that the calls can be inspected once a clone was handed to the deployer.
+#[derive(Clone, Default)]
+pub struct FakeToolchain {
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeToolchain {
+    /// Makes the contracts fail to compile.
+    pub fn fail_build(self) -> Self {
+        self.state.lock().unwrap().failing_build = true;
+        self
+    }
+
+    /// Makes the scripts run on a chain fail.
+    pub fn fail_on(
+        self,
+        chain: &str,
+    ) -> Self {
+        self.state.lock().unwrap().failing_chains.push(chain.to_string());
+        self
+    }
+
+    /// Makes the scripts run on a chain write their broadcast for another chain id, and the RPC of the chain report it,
+    /// as when the RPC URL points to another network. Chains unknown to the chain registry need one to be reachable.
+    pub fn report_chain_id(
+        self,
+        chain: &str,
+        chain_id: u64,
+    ) -> Self {
+        self.state.lock().unwrap().chain_ids.insert(chain.to_string(), chain_id);
+        self
+    }
+
+    /// Makes the scripts deploy no code at an address.
+    pub fn missing_code(
+        self,
+        address: &str,
+    ) -> Self {
+        self.state.lock().unwrap().missing_code.push(address.to_string());
+        self
+    }
+
+    /// Makes an address already have code on a chain, as when a previous deployment went through.
+    pub fn deployed_on(
+        self,
+        chain: &str,
+        address: &str,
+    ) -> Self {
+        self.state.lock().unwrap().code.entry(chain.to_string()).or_default().push(address.to_string());
+        self
+    }
+
+    /// Makes the explorers rate limit the given number of verifications, before accepting the others.
+    pub fn rate_limit(
+        self,
+        verifications: usize,
+    ) -> Self {
+        self.state.lock().unwrap().rate_limited = verifications;
+        self
+    }
+
+    /// Makes `cast wallet address` decrypt a keystore account to an address.
+    pub fn account(
+        self,
+        account: &str,
+        address: &str,
+    ) -> Self {
+        self.state.lock().unwrap().accounts.insert(account.to_string(), address.to_string());
+        self
+    }
+
+    /// Makes the verifications with an API key panic.
+    pub fn panic_on_verify(
+        self,
+        api_key: &str,
+    ) -> Self {
+        self.state.lock().unwrap().panicking_keys.push(api_key.to_string());
+        self
+    }
+
+    /// The number of forks started.
+    pub fn forks(&self) -> usize {
+        self.state.lock().unwrap().forks.len()
+    }
+
+    /// Every `forge build` run, in order.
+    pub fn builds(&self) -> Vec<Call> {
+        self.state.lock().unwrap().builds.clone()
+    }
+
+    /// Every `forge script` run, in order.
+    pub fn scripts(&self) -> Vec<Call> {
+        self.state.lock().unwrap().scripts.clone()
+    }
+
+    /// Every `forge script` run that broadcasted, in order.
+    pub fn broadcasts(&self) -> Vec<Call> {
+        self.scripts().into_iter().filter(|call| call.args.iter().any(|arg| arg == "--broadcast")).collect()
+    }
+
+    /// The private keys of the keystores the scripts were run with, decrypted as forge would.
+    pub fn keystore_keys(&self) -> Vec<String> {
+        self.state.lock().unwrap().keystore_keys.clone()
+    }
+
+    /// Every `forge verify-contract` run, in order.
+    pub fn verifications(&self) -> Vec<Call> {
+        self.state.lock().unwrap().verifications.clone()
+    }
+}
+
+impl ToolchainRunner for FakeToolchain {
+    fn run_script(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        let call = Call::new(args, env);
+        let rpc_url = call.flag("--rpc-url").expect("the chain is passed with --rpc-url");
+        let broadcast_dir = call.env.get("FOUNDRY_BROADCAST").cloned().unwrap_or_else(|| "broadcast".to_string());
+        let keystore_key = call.flag("--keystore").map(|keystore| {
+            let password = fs::read_to_string(call.flag("--password-file").unwrap()).unwrap();
+            format!("0x{}", hex::encode(eth_keystore::decrypt_key(keystore, password).unwrap()))
+        });
+        let mut state = self.state.lock().unwrap();
+        state.keystore_keys.extend(keystore_key);
+        state.scripts.push(call);
+
+        let (network, chain) = state.network(&rpc_url);
+        if state.failing_chains.contains(&chain) {
+            return Ok(ToolOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: "Error: script failed".to_string(),
+            });
+        }
+
+        let chain_id = state.chain_ids.get(&chain).copied().or_else(|| ChainRegistry::chain_id(&chain)).unwrap_or(1);
+        let script_name = args[0].split(':').next().unwrap().rsplit('/').next().unwrap();
+        let mut dir = PathBuf::from(broadcast_dir).join(script_name).join(chain_id.to_string());
+        if args.iter().any(|arg| arg == "--broadcast") {
+            let deployed = [LOCKUP, BATCH_LOCKUP].into_iter().filter(|a| !state.missing_code.iter().any(|m| m == a));
+            let deployed: Vec<String> = deployed.map(String::from).collect();
+            state.code.entry(network).or_default().extend(deployed);
+        } else {
+            dir = dir.join("dry-run");
+        }
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("run-latest.json"), broadcast(chain_id)).unwrap();
+
+        Ok(ToolOutput { success: true, stdout: format!("Chain {}\n", chain_id), stderr: String::new() })
+    }
+
+    fn verify_contract(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        let mut state = self.state.lock().unwrap();
+        let call = Call::new(args, env);
+        let panics = call.env.get("ETHERSCAN_API_KEY").is_some_and(|key| state.panicking_keys.contains(key));
+        state.verifications.push(call);
+        if panics {
+            // Release the state first, so that it isn't poisoned for the other chains
+            drop(state);
+            panic!("the verification panicked");
+        }
+        if state.rate_limited > 0 {
+            state.rate_limited -= 1;
+            return Ok(ToolOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: "Error: Max calls per sec rate limit reached (5/sec)".to_string(),
+            });
+        }
+        Ok(ToolOutput { success: true, stdout: "Contract successfully verified\n".to_string(), stderr: String::new() })
+    }
+
+    fn build(
+        &self,
+        args: &[String],
+        env: &[(String, String)],
+    ) -> Result<ToolOutput, Error> {
+        let mut state = self.state.lock().unwrap();
+        state.builds.push(Call::new(args, env));
+        if state.failing_build {
+            return Ok(ToolOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: "Error: Compiler run failed".to_string(),
+            });
+        }
+        Ok(ToolOutput { success: true, ..ToolOutput::default() })
+    }
+
+    fn start_fork(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ForkNode, Error> {
+        let mut state = self.state.lock().unwrap();
+        let (network, chain) = state.network(rpc_url);
+        let url = format!("fork://{}/{}", state.forks.len(), chain);
+
+        // The fork starts with the code of the chain it forks
+        let code = state.code.get(&network).cloned().unwrap_or_default();
+        state.code.insert(url.clone(), code);
+        state.forks.insert(url.clone(), chain);
+        Ok(ForkNode::new(url, None))
+    }
+
+    fn code_at(
+        &self,
+        address: &str,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error> {
+        let state = self.state.lock().unwrap();
+        let (network, _) = state.network(rpc_url);
+        let has_code = state.code.get(&network).is_some_and(|addresses| addresses.iter().any(|a| a == address));
+        let code = if has_code { "0x6080" } else { "0x" };
+        Ok(ToolOutput { success: true, stdout: format!("{}\n", code), stderr: String::new() })
+    }
+
+    fn chain_id(
+        &self,
+        rpc_url: &str,
+    ) -> Result<ToolOutput, Error> {
+        let state = self.state.lock().unwrap();
+        let (_, chain) = state.network(rpc_url);
+        Ok(match state.chain_ids.get(&chain).copied().or_else(|| ChainRegistry::chain_id(&chain)) {
+            Some(chain_id) => ToolOutput { success: true, stdout: format!("{}\n", chain_id), stderr: String::new() },
+            None => ToolOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: format!("Error: error sending request for url ({})", rpc_url),
+            },
+        })
+    }
+
+    fn wallet_address(
+        &self,
+        account: &str,
+    ) -> Result<ToolOutput, Error> {
+        Ok(match self.state.lock().unwrap().accounts.get(account) {
+            Some(address) => ToolOutput { success: true, stdout: format!("{}\n", address), stderr: String::new() },
+            None => ToolOutput::default(),
+        })
+    }
+}
+
+/// The broadcast of a run deploying `SablierLockup` and `SablierBatchLockup`, as written by forge.
+pub fn broadcast(chain_id: u64) -> String {
+    let hash = |n: u8| format!("0x{}", format!("{:02x}", n).repeat(32));
+    let transaction = |n: u8, name: &str, address: &str, arguments: &str| {
+        format!(
+            r#"{{"hash":"{}","transactionType":"CREATE2","contractName":"{}","contractAddress":"{}","arguments":{},"transaction":{{"from":"{}","input":"0x6080"}}}}"#,
+            hash(n),
+            name,
+            address,
+            arguments,
+            ADMIN
+        )
+    };
+    let receipt = |n: u8, address: &str| {
+        format!(r#"{{"transactionHash":"{}","blockNumber":"0x1669749","contractAddress":"{}"}}"#, hash(n), address)
+    };
+
+    format!(
+        r#"{{"transactions":[{},{}],"receipts":[{},{}],"libraries":[],"timestamp":1700000000,"chain":{}}}"#,
+        transaction(1, "SablierLockup", LOCKUP, &format!(r#"["{}"]"#, ADMIN)),
+        transaction(2, "SablierBatchLockup", BATCH_LOCKUP, "null"),
+        receipt(1, LOCKUP),
+        receipt(2, BATCH_LOCKUP),
+        chain_id
+    )
+}
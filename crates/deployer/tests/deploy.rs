@@ -0,0 +1,519 @@
+mod common;
+
+use common::{FakeToolchain, Project, ADMIN, BATCH_LOCKUP, LOCKUP};
+use deployer::{
+    utils::{forge::ScriptOptions, signer::Signer},
+    Config, DeployOptions, Deployer, Error, Exporter, FoundryConfig,
+};
+use std::{env, fs, path::PathBuf, process::Command, time::Duration};
+
+const SCRIPT: &str = "DeployProtocol.s.sol";
+
+fn chains(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn deployer(
+    toolchain: &FakeToolchain,
+    options: DeployOptions,
+) -> Deployer {
+    let mut config = Config::default();
+    config.sdk.dir = "sdk".to_string();
+    config.verify.backoff_ms = 1;
+    let foundry = FoundryConfig::load("default").unwrap();
+    let script = ScriptOptions { name: SCRIPT.to_string(), ..ScriptOptions::default() };
+
+    Deployer::new(config, foundry, script, options).with_runner(toolchain.clone())
+}
+
+fn broadcast_options() -> DeployOptions {
+    DeployOptions { broadcast: true, verify_delay: Duration::ZERO, ..DeployOptions::default() }
+}
+
+#[test]
+fn deploys_on_every_chain_and_exports_the_deployments() {
+    let project = Project::new("multi-chain");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions {
+        copy_broadcast: true,
+        print_deployment: Some(PathBuf::from("deployments.ts")),
+        ..broadcast_options()
+    };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum", "chiliz"])).unwrap();
+
+    let scripts = toolchain.scripts();
+    let rpc_urls: Vec<String> = scripts.iter().filter_map(|call| call.flag("--rpc-url")).collect();
+    assert_eq!(rpc_urls, ["sepolia", "arbitrum", "chiliz", "sepolia", "arbitrum", "chiliz"]);
+    assert_eq!(toolchain.broadcasts().len(), 3);
+    for call in &scripts {
+        assert_eq!(call.args[0], format!("script/{}", SCRIPT));
+        assert_eq!(call.args.contains(&"--legacy".to_string()), call.flag("--rpc-url").unwrap() == "chiliz");
+        assert_eq!(call.env["FOUNDRY_PROFILE"], "default");
+    }
+
+    for chain in ["sepolia", "arbitrum", "chiliz"] {
+        let copy = project.dir.join(format!("sdk/deployments/lockup/v2.0.0/broadcasts/{}.json", chain));
+        assert!(copy.exists(), "{} was not copied", copy.display());
+    }
+
+    let deployments = fs::read_to_string(project.dir.join("deployments.ts")).unwrap();
+    for chain in ["sepolia", "arbitrum", "chiliz"] {
+        assert!(deployments.contains(&format!("get(chains.{}.id", chain)), "{}", deployments);
+    }
+    assert!(deployments.contains(LOCKUP) && deployments.contains(BATCH_LOCKUP));
+}
+
+#[test]
+fn reports_failed_chains_and_deploys_the_others() {
+    let _project = Project::new("failure");
+    let toolchain = FakeToolchain::default().fail_on("arbitrum");
+    let options = DeployOptions { verify: true, ..broadcast_options() };
+
+    let result = deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum", "chiliz"]));
+
+    match result {
+        Err(Error::FailedChains(failed)) => assert_eq!(failed, ["arbitrum"]),
+        other => panic!("expected the arbitrum deployment to fail, got {:?}", other),
+    }
+    // The dry run on arbitrum fails, so it is not broadcasted
+    assert_eq!(toolchain.scripts().len(), 5);
+    assert_eq!(toolchain.broadcasts().len(), 2);
+
+    // The chain the script failed on is not verified
+    let mut keys: Vec<String> =
+        toolchain.verifications().iter().map(|call| call.env["ETHERSCAN_API_KEY"].clone()).collect();
+    keys.sort();
+    assert_eq!(keys, ["chiliz-key", "chiliz-key", "sepolia-key", "sepolia-key"]);
+}
+
+#[test]
+fn verifies_every_contract_on_every_chain() {
+    let _project = Project::new("verification");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions { verify: true, ..broadcast_options() };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum"])).unwrap();
+
+    // The chains are verified concurrently, each one contract at a time
+    let verifications = toolchain.verifications();
+    assert_eq!(verifications.len(), 4);
+    for chain in ["sepolia", "arbitrum"] {
+        let key = format!("{}-key", chain);
+        let addresses: Vec<&str> = verifications
+            .iter()
+            .filter(|call| call.env["ETHERSCAN_API_KEY"] == key)
+            .map(|call| call.args[0].as_str())
+            .collect();
+        assert_eq!(addresses, [LOCKUP, BATCH_LOCKUP], "on {}", chain);
+    }
+    for call in &verifications {
+        assert_eq!(call.env["FOUNDRY_PROFILE"], "default");
+        match call.args[0].as_str() {
+            LOCKUP => {
+                assert_eq!(call.args[1], "src/SablierLockup.sol:SablierLockup");
+                let encoded = format!("0x{:0>64}", ADMIN.trim_start_matches("0x"));
+                assert_eq!(call.flag("--constructor-args").unwrap(), encoded);
+            }
+            BATCH_LOCKUP => {
+                assert_eq!(call.args[1], "src/SablierBatchLockup.sol:SablierBatchLockup");
+                assert!(call.flag("--constructor-args").is_none());
+            }
+            address => panic!("unexpected verification of {}", address),
+        }
+    }
+}
+
+#[test]
+fn retries_the_verifications_rate_limited_by_the_explorers() {
+    let _project = Project::new("rate-limit");
+    let toolchain = FakeToolchain::default().rate_limit(3);
+    let options = DeployOptions { verify: true, ..broadcast_options() };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum"])).unwrap();
+
+    // Every rate limited verification is retried until it goes through
+    let verifications = toolchain.verifications();
+    assert_eq!(verifications.len(), 7);
+    for address in [LOCKUP, BATCH_LOCKUP] {
+        for chain in ["sepolia", "arbitrum"] {
+            let key = format!("{}-key", chain);
+            let attempts =
+                verifications.iter().filter(|call| call.args[0] == address && call.env["ETHERSCAN_API_KEY"] == key);
+            assert!(attempts.count() >= 1, "{} was not verified on {}", address, chain);
+        }
+    }
+}
+
+#[test]
+fn fails_once_deployed_when_contracts_couldnt_be_verified() {
+    let project = Project::new("verification-failure");
+    let toolchain = FakeToolchain::default().rate_limit(usize::MAX);
+    let options = DeployOptions { copy_broadcast: true, verify: true, ..broadcast_options() };
+
+    let result = deployer(&toolchain, options).deploy(&chains(&["sepolia"]));
+
+    // The deployment went through, only the verification failed
+    assert_eq!(toolchain.broadcasts().len(), 1);
+    assert!(project.dir.join("sdk/deployments/lockup/v2.0.0/broadcasts/sepolia.json").exists());
+    match result {
+        Err(Error::FailedVerifications(failed)) => {
+            assert_eq!(failed, ["SablierLockup on sepolia", "SablierBatchLockup on sepolia"]);
+        }
+        other => panic!("expected failed verifications, got {:?}", other),
+    }
+}
+
+#[test]
+fn runs_nothing_on_the_chains_when_the_contracts_dont_compile() {
+    let _project = Project::new("build-failure");
+    let toolchain = FakeToolchain::default().fail_build();
+
+    let result = deployer(&toolchain, broadcast_options()).deploy(&chains(&["sepolia", "arbitrum"]));
+
+    match result {
+        Err(Error::BuildFailed { stderr }) => assert!(stderr.contains("Compiler run failed"), "{}", stderr),
+        other => panic!("expected the build to fail, got {:?}", other),
+    }
+    assert_eq!(toolchain.builds().len(), 1);
+    assert!(toolchain.scripts().is_empty());
+}
+
+#[test]
+fn stops_when_a_broadcast_is_for_another_chain() {
+    let _project = Project::new("chain-id");
+    let toolchain = FakeToolchain::default().report_chain_id("arbitrum", 1);
+
+    let result = deployer(&toolchain, broadcast_options()).deploy(&chains(&["arbitrum", "sepolia"]));
+
+    match result {
+        Err(Error::ChainIdMismatch { chain, expected, actual, .. }) => {
+            assert_eq!((chain.as_str(), expected, actual), ("arbitrum", 42161, 1));
+        }
+        other => panic!("expected a chain id mismatch, got {:?}", other),
+    }
+    assert_eq!(toolchain.scripts().len(), 1);
+}
+
+#[test]
+fn stops_when_a_broadcast_is_for_another_chain_despite_an_earlier_one() {
+    let _project = Project::new("chain-id-stale");
+    let toolchain = FakeToolchain::default();
+
+    // An earlier run left a broadcast for arbitrum, then its RPC URL was pointed to another network
+    let options = DeployOptions { verify_delay: Duration::ZERO, ..DeployOptions::default() };
+    deployer(&toolchain, options).deploy(&chains(&["arbitrum"])).unwrap();
+    let stale = PathBuf::from(format!("broadcast/{}/42161/dry-run/run-latest.json", SCRIPT));
+    let content = fs::read_to_string(&stale).unwrap();
+
+    let toolchain = toolchain.report_chain_id("arbitrum", 1);
+    let options = DeployOptions { verify: true, verify_delay: Duration::ZERO, ..DeployOptions::default() };
+    let result = deployer(&toolchain, options).deploy(&chains(&["arbitrum"]));
+
+    match result {
+        Err(Error::ChainIdMismatch { chain, expected, actual, .. }) => {
+            assert_eq!((chain.as_str(), expected, actual), ("arbitrum", 42161, 1));
+        }
+        other => panic!("expected a chain id mismatch, got {:?}", other),
+    }
+    assert_eq!(fs::read_to_string(&stale).unwrap(), content);
+    assert!(toolchain.verifications().is_empty());
+}
+
+#[test]
+fn asks_the_rpc_for_the_id_of_chains_unknown_to_the_registry() {
+    let project = Project::new("unknown-chain-id");
+    let toolchain = FakeToolchain::default().report_chain_id("devnet", 1337);
+
+    deployer(&toolchain, broadcast_options()).deploy(&chains(&["devnet"])).unwrap();
+
+    assert!(project.dir.join(format!("broadcast/{}/1337/run-latest.json", SCRIPT)).exists());
+}
+
+#[test]
+fn runs_nothing_when_the_chain_id_cant_be_queried() {
+    let _project = Project::new("unreachable-chain-id");
+    let toolchain = FakeToolchain::default();
+
+    let error = deployer(&toolchain, broadcast_options()).deploy(&chains(&["sepolia", "devnet"])).unwrap_err();
+
+    assert!(matches!(error, Error::Rpc { ref chain, .. } if chain == "devnet"), "{:?}", error);
+    assert!(toolchain.builds().is_empty());
+    assert!(toolchain.scripts().is_empty());
+}
+
+#[test]
+fn passes_a_private_key_from_the_environment_in_a_temporary_keystore() {
+    let _project = Project::new("private-key");
+    let toolchain = FakeToolchain::default();
+    // The first anvil account
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    env::set_var("DEPLOYER_TEST_PRIVATE_KEY", private_key);
+    let options = DeployOptions {
+        sender: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
+        signer: Signer::PrivateKeyEnv("DEPLOYER_TEST_PRIVATE_KEY".to_string()),
+        ..broadcast_options()
+    };
+
+    let result = deployer(&toolchain, options).deploy(&chains(&["sepolia"]));
+    env::remove_var("DEPLOYER_TEST_PRIVATE_KEY");
+    result.unwrap();
+
+    let broadcasts = toolchain.broadcasts();
+    let call = &broadcasts[0];
+    assert!(call.args.iter().chain(call.env.values()).all(|word| !word.contains(&private_key[2..])));
+    assert_eq!(toolchain.keystore_keys(), [private_key]);
+
+    // The keystore is removed once the deployment ends
+    assert!(!PathBuf::from(call.flag("--keystore").unwrap()).exists());
+}
+
+#[test]
+fn checks_the_sender_against_a_keystore_without_its_address() {
+    let project = Project::new("keystore-address");
+    let keystores = project.dir.join(".foundry/keystores");
+    fs::create_dir_all(&keystores).unwrap();
+    fs::write(keystores.join("deployer"), r#"{"crypto":{},"id":"1","version":3}"#).unwrap();
+    let toolchain = FakeToolchain::default().account("deployer", ADMIN);
+    let options = |sender: &str| DeployOptions {
+        sender: sender.to_string(),
+        signer: Signer::Account("deployer".to_string()),
+        ..broadcast_options()
+    };
+
+    // The keystores are looked up in the home directory
+    let home = env::var_os("HOME");
+    env::set_var("HOME", &project.dir);
+    let mismatch = deployer(&toolchain, options(LOCKUP)).deploy(&chains(&["sepolia"]));
+    let deployment = deployer(&toolchain, options(ADMIN)).deploy(&chains(&["sepolia"]));
+    match home {
+        Some(home) => env::set_var("HOME", home),
+        None => env::remove_var("HOME"),
+    }
+
+    assert!(matches!(mismatch, Err(Error::SignerMismatch { ref signer, .. }) if signer == ADMIN), "{:?}", mismatch);
+    deployment.unwrap();
+    assert_eq!(toolchain.broadcasts().len(), 1);
+}
+
+#[test]
+fn only_prints_the_commands_with_show() {
+    let _project = Project::new("show");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions { show_cli: true, verify: true, ..broadcast_options() };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "chiliz"])).unwrap();
+
+    assert!(toolchain.scripts().is_empty());
+    assert!(toolchain.verifications().is_empty());
+}
+
+#[test]
+fn writes_the_shown_commands_to_a_script() {
+    let project = Project::new("emit-script");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions {
+        copy_broadcast: true,
+        emit_script: Some(PathBuf::from("run.sh")),
+        show_cli: true,
+        signer: Signer::Account("deployer".to_string()),
+        verify: true,
+        verify_delay: Duration::from_secs(10),
+        ..broadcast_options()
+    };
+
+    // A dry run of the user is left as is
+    let dry_run = PathBuf::from(format!("broadcast/{}/11155111/dry-run/run-latest.json", SCRIPT));
+    fs::create_dir_all(dry_run.parent().unwrap()).unwrap();
+    fs::write(&dry_run, "{}").unwrap();
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "chiliz"])).unwrap();
+
+    // Nothing runs, not even a simulation
+    assert!(toolchain.scripts().is_empty());
+    assert!(toolchain.verifications().is_empty());
+    assert_eq!(fs::read_to_string(&dry_run).unwrap(), "{}");
+    let script = fs::read_to_string("run.sh").unwrap();
+    assert!(script.starts_with("#!/usr/bin/env bash\n"), "{}", script);
+    assert!(script.contains(&format!("cd {}\n", project.dir.display())), "{}", script);
+    for chain in ["sepolia", "chiliz"] {
+        let deploy = format!("FOUNDRY_PROFILE=default forge script script/{} --rpc-url {} --broadcast", SCRIPT, chain);
+        assert!(script.contains(&deploy), "{}", script);
+        let copy =
+            format!("deployer copy {} --script {} --run latest --sdk-dir sdk --export-format full\n", chain, SCRIPT);
+        assert!(script.contains(&copy), "{}", script);
+    }
+    assert!(script.contains("--account deployer"), "{}", script);
+    assert!(!script.contains("--private-key"), "{}", script);
+    assert!(script.contains("# Build the contracts\nFOUNDRY_PROFILE=default forge build\n"), "{}", script);
+    assert!(script.contains("sleep 10\n"), "{}", script);
+
+    // The contracts are verified from the broadcasts, once the deployment ran
+    for chain in ["sepolia", "chiliz"] {
+        let verify = format!(
+            "# Verify the contracts on {}\ndeployer verify {} --script {} --run latest --profile default\n",
+            chain, chain, SCRIPT
+        );
+        assert!(script.contains(&verify), "{}", script);
+    }
+    assert!(!script.contains("forge verify-contract"), "{}", script);
+    assert!(!script.contains("sepolia-key") && !script.contains("chiliz-key"), "{}", script);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata("run.sh").unwrap().permissions().mode() & 0o777, 0o755);
+    }
+    // The script must at least be valid bash
+    if let Ok(status) = Command::new("bash").args(["-n", "run.sh"]).status() {
+        assert!(status.success());
+    }
+}
+
+#[test]
+fn rejects_a_private_key_in_the_emitted_script() {
+    let _project = Project::new("emit-private-key");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions {
+        emit_script: Some(PathBuf::from("run.sh")),
+        show_cli: true,
+        signer: Signer::PrivateKeyEnv("PRIVATE_KEY".to_string()),
+        ..broadcast_options()
+    };
+
+    let error = deployer(&toolchain, options).deploy(&chains(&["sepolia"])).unwrap_err();
+
+    assert!(matches!(error, Error::PrivateKeyInScript { ref var } if var == "PRIVATE_KEY"), "{:?}", error);
+    assert!(!PathBuf::from("run.sh").exists());
+}
+
+#[test]
+fn checks_the_deployment_on_forks_before_broadcasting() {
+    let project = Project::new("fork-check");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions { fork_check: true, ..broadcast_options() };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum"])).unwrap();
+
+    assert_eq!(toolchain.forks(), 2);
+    let rpc_urls: Vec<String> = toolchain.broadcasts().iter().filter_map(|call| call.flag("--rpc-url")).collect();
+    assert_eq!(rpc_urls, ["fork://0/sepolia", "fork://1/arbitrum", "sepolia", "arbitrum"]);
+
+    let broadcasts = toolchain.broadcasts();
+    let (fork_runs, runs) = broadcasts.split_at(2);
+    for call in fork_runs {
+        assert!(call.args.contains(&"--broadcast".to_string()) && call.args.contains(&"--unlocked".to_string()));
+        assert_eq!(call.env["FOUNDRY_BROADCAST"], "cache/fork-check");
+    }
+    for call in runs {
+        assert!(!call.args.contains(&"--unlocked".to_string()));
+        assert!(!call.env.contains_key("FOUNDRY_BROADCAST"));
+    }
+    assert!(project.dir.join(format!("cache/fork-check/{}/11155111/run-latest.json", SCRIPT)).exists());
+    assert!(project.dir.join(format!("broadcast/{}/11155111/run-latest.json", SCRIPT)).exists());
+}
+
+#[test]
+fn broadcasts_nothing_when_a_fork_check_fails() {
+    let project = Project::new("fork-check-failure");
+    let toolchain = FakeToolchain::default().fail_on("arbitrum").missing_code(BATCH_LOCKUP);
+    let options = DeployOptions { fork_check: true, ..broadcast_options() };
+
+    let result = deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum", "chiliz"]));
+
+    // The dry run on arbitrum fails already, so it is not checked on a fork
+    match result {
+        Err(Error::ForkCheckFailed(failed)) => assert_eq!(failed, ["sepolia", "chiliz"]),
+        other => panic!("expected the fork check to fail, got {:?}", other),
+    }
+    assert!(toolchain.broadcasts().iter().all(|call| call.flag("--rpc-url").unwrap().starts_with("fork://")));
+    assert!(!project.dir.join(format!("broadcast/{}/11155111/run-latest.json", SCRIPT)).exists());
+}
+
+#[test]
+fn skips_the_chains_where_the_contracts_already_exist() {
+    let project = Project::new("already-deployed");
+    let toolchain = FakeToolchain::default().deployed_on("sepolia", LOCKUP).deployed_on("sepolia", BATCH_LOCKUP);
+    let options = DeployOptions { verify: true, ..broadcast_options() };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "arbitrum"])).unwrap();
+
+    let rpc_urls: Vec<String> = toolchain.broadcasts().iter().filter_map(|call| call.flag("--rpc-url")).collect();
+    assert_eq!(rpc_urls, ["arbitrum"]);
+    assert!(toolchain.verifications().iter().all(|call| call.env["ETHERSCAN_API_KEY"] == "arbitrum-key"));
+    assert!(!project.dir.join(format!("broadcast/{}/11155111/run-latest.json", SCRIPT)).exists());
+}
+
+#[test]
+fn reports_the_remaining_contracts_of_a_partial_deployment() {
+    let _project = Project::new("partial-deployment");
+    let toolchain = FakeToolchain::default().deployed_on("arbitrum", LOCKUP);
+
+    let result = deployer(&toolchain, broadcast_options()).deploy(&chains(&["sepolia", "arbitrum"]));
+
+    match result {
+        Err(Error::FailedChains(failed)) => assert_eq!(failed, ["arbitrum"]),
+        other => panic!("expected the arbitrum deployment to be partial, got {:?}", other),
+    }
+    let rpc_urls: Vec<String> = toolchain.broadcasts().iter().filter_map(|call| call.flag("--rpc-url")).collect();
+    assert_eq!(rpc_urls, ["sepolia"]);
+}
+
+#[test]
+fn deploys_and_verifies_on_zkevm_chains_in_zksync_mode() {
+    let _project = Project::new("zkevm");
+    let toolchain = FakeToolchain::default();
+    let options = DeployOptions { fork_check: true, verify: true, ..broadcast_options() };
+
+    deployer(&toolchain, options).deploy(&chains(&["sepolia", "zksync"])).unwrap();
+
+    // anvil can't fork zkEVM chains, so only sepolia is checked on a fork
+    assert_eq!(toolchain.forks(), 1);
+    let builds: Vec<(Vec<String>, Option<String>)> =
+        toolchain.builds().into_iter().map(|call| (call.args, call.env.get("FOUNDRY_OUT").cloned())).collect();
+    assert_eq!(builds, [(Vec::new(), None), (vec!["--zksync".to_string()], Some("zkout".to_string()))]);
+    for call in toolchain.scripts() {
+        let is_zksync = call.flag("--rpc-url").unwrap() == "zksync";
+        assert_eq!(call.args.contains(&"--zksync".to_string()), is_zksync);
+        assert_eq!(call.env.get("FOUNDRY_OUT").map(String::as_str), is_zksync.then_some("zkout"));
+    }
+
+    let verifications = toolchain.verifications();
+    assert_eq!(verifications.len(), 4);
+    let (zksync, sepolia): (Vec<_>, Vec<_>) =
+        verifications.iter().partition(|call| call.args.contains(&"--zksync".to_string()));
+    assert_eq!(zksync.len(), 2);
+    for call in zksync {
+        assert_eq!(call.flag("--verifier").unwrap(), "zksync");
+        assert!(call.flag("--verifier-url").unwrap().contains("zksync"));
+        assert_eq!(call.env["FOUNDRY_OUT"], "zkout");
+        assert!(!call.env.contains_key("ETHERSCAN_API_KEY"));
+    }
+    assert!(sepolia.iter().all(|call| call.env["ETHERSCAN_API_KEY"] == "sepolia-key"));
+}
+
+#[test]
+fn reports_the_contracts_deployed_at_different_addresses() {
+    let project = Project::new("address-mismatch");
+    let toolchain = FakeToolchain::default();
+
+    deployer(&toolchain, broadcast_options()).deploy(&chains(&["sepolia", "arbitrum", "zksync"])).unwrap();
+
+    // Deploy the lockup elsewhere on arbitrum and zkSync, which derives its CREATE2 addresses differently
+    let other = "0x00000000000000000000000000000000000000aa";
+    for chain_id in [42161, 324] {
+        let path = project.dir.join(format!("broadcast/{}/{}/run-latest.json", SCRIPT, chain_id));
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace(LOCKUP, other)).unwrap();
+    }
+
+    let config = Config::default();
+    let foundry = FoundryConfig::load("default").unwrap();
+    let chains = chains(&["sepolia", "arbitrum", "zksync"]);
+    let chain_ids = foundry.chain_ids(&chains, &toolchain).unwrap();
+    let mismatches = Exporter::new(&config, SCRIPT, &chain_ids).address_mismatches(&chains).unwrap();
+    assert_eq!(
+        mismatches,
+        [format!("SablierLockup has different addresses across chains: {} on sepolia, {} on arbitrum", LOCKUP, other)]
+    );
+}
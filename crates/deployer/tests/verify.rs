@@ -0,0 +1,155 @@
+mod common;
+
+use common::{FakeToolchain, Project, ADMIN, BATCH_LOCKUP, LOCKUP};
+use deployer::{
+    utils::{batch, broadcast::BroadcastRun, verify::VerifySettings},
+    Error, FoundryConfig, ToolchainRunner, Verifier, VerifyTarget,
+};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+fn verify(
+    toolchain: &FakeToolchain,
+    targets: &[VerifyTarget],
+) -> Result<(), Error> {
+    let foundry = FoundryConfig::load("default").unwrap();
+    let chains: Vec<String> = targets.iter().map(|target| target.chain.clone()).collect();
+    let chain_ids = foundry.chain_ids(&chains, toolchain).unwrap();
+    let settings = VerifySettings::default();
+
+    Verifier::new(toolchain, &foundry, &settings, false).verify_targets(targets, &chain_ids)
+}
+
+#[test]
+fn verifies_a_contract_without_a_broadcast() {
+    let _project = Project::new("verify-address");
+    let toolchain = FakeToolchain::default();
+    let target = VerifyTarget {
+        chain: "sepolia".to_string(),
+        address: LOCKUP.to_string(),
+        contract: "SablierLockup".to_string(),
+        args: vec![Value::from(ADMIN)],
+        libraries: Vec::new(),
+    };
+
+    verify(&toolchain, &[target]).unwrap();
+
+    let verifications = toolchain.verifications();
+    assert_eq!(verifications.len(), 1);
+    let call = &verifications[0];
+    assert_eq!(call.args[..2], [LOCKUP, "src/SablierLockup.sol:SablierLockup"]);
+    assert_eq!(call.flag("--constructor-args").unwrap(), format!("0x{:0>64}", ADMIN.trim_start_matches("0x")));
+    assert_eq!(call.env["ETHERSCAN_API_KEY"], "sepolia-key");
+}
+
+#[test]
+fn verifies_a_csv_batch_across_chains() {
+    let _project = Project::new("verify-csv");
+    let toolchain = FakeToolchain::default();
+    let csv = format!(
+        "chain,address,contract,args\n{},{},SablierLockup,{}\n\n# Deployed by hand\n{},{},src/batch/Batch.sol:Batch\n",
+        "sepolia", LOCKUP, ADMIN, "arbitrum", BATCH_LOCKUP
+    );
+    fs::write("contracts.csv", csv).unwrap();
+
+    let targets = batch::load(Path::new("contracts.csv")).unwrap();
+    verify(&toolchain, &targets).unwrap();
+
+    let mut verifications = toolchain.verifications();
+    verifications.sort_by_key(|call| call.env["ETHERSCAN_API_KEY"].clone());
+    let contracts: Vec<(&str, &str)> =
+        verifications.iter().map(|call| (call.args[1].as_str(), call.env["ETHERSCAN_API_KEY"].as_str())).collect();
+    assert_eq!(
+        contracts,
+        [("src/batch/Batch.sol:Batch", "arbitrum-key"), ("src/SablierLockup.sol:SablierLockup", "sepolia-key")]
+    );
+    assert!(verifications[1].flag("--constructor-args").is_some());
+}
+
+#[test]
+fn verifies_a_json_batch_with_libraries() {
+    let _project = Project::new("verify-json");
+    let toolchain = FakeToolchain::default();
+    let json = format!(
+        r#"[{{ "chain": "chiliz", "address": "{}", "contract": "SablierBatchLockup", "libraries": ["src/Helpers.sol:Helpers:{}"] }}]"#,
+        BATCH_LOCKUP, LOCKUP
+    );
+    fs::write("contracts.json", json).unwrap();
+
+    let targets = batch::load(Path::new("contracts.json")).unwrap();
+    verify(&toolchain, &targets).unwrap();
+
+    let call = &toolchain.verifications()[0];
+    assert_eq!(call.flag("--libraries").unwrap(), format!("src/Helpers.sol:Helpers:{}", LOCKUP));
+    assert!(call.flag("--constructor-args").is_none());
+    assert_eq!(call.env["ETHERSCAN_API_KEY"], "chiliz-key");
+}
+
+#[test]
+fn verifies_the_selected_run_of_a_script() {
+    let _project = Project::new("verify-run");
+    let toolchain = FakeToolchain::default();
+    let args: Vec<String> =
+        ["script/DeployProtocol.s.sol", "--rpc-url", "sepolia", "--broadcast"].map(String::from).into();
+    toolchain.run_script(&args, &[]).unwrap();
+
+    // Keep the run as a past one, as forge does when the script runs again
+    let dir = Path::new("broadcast/DeployProtocol.s.sol/11155111");
+    fs::rename(dir.join("run-latest.json"), dir.join("run-1700000000.json")).unwrap();
+    fs::copy(dir.join("run-1700000000.json"), "sepolia.json").unwrap();
+
+    let foundry = FoundryConfig::load("default").unwrap();
+    let chains = vec!["sepolia".to_string()];
+    let chain_ids = foundry.chain_ids(&chains, &toolchain).unwrap();
+    let settings = VerifySettings::default();
+    let verifier = Verifier::new(&toolchain, &foundry, &settings, false);
+
+    let past = BroadcastRun::parse("run-1700000000.json").unwrap();
+    assert_eq!(past, BroadcastRun::Timestamp(1700000000));
+    assert!(verifier.verify_all("DeployProtocol.s.sol", &past, &chains, &chain_ids).is_empty());
+    assert_eq!(toolchain.verifications().len(), 2);
+
+    let path = BroadcastRun::parse("{chain}.json").unwrap();
+    assert!(verifier.verify_all("DeployProtocol.s.sol", &path, &chains, &chain_ids).is_empty());
+    assert_eq!(toolchain.verifications().len(), 4);
+
+    // The latest run was moved away, so there is nothing to verify
+    let failed = verifier.verify_all("DeployProtocol.s.sol", &BroadcastRun::Latest, &chains, &chain_ids);
+    assert_eq!(failed, ["the contracts on sepolia"]);
+    assert_eq!(toolchain.verifications().len(), 4);
+}
+
+#[test]
+fn rejects_a_batch_with_an_invalid_address() {
+    let _project = Project::new("verify-invalid");
+    fs::write("contracts.csv", "sepolia,0x1234,SablierLockup\n").unwrap();
+
+    match batch::load(Path::new("contracts.csv")) {
+        Err(Error::InvalidArgument(message)) => assert!(message.contains("0x1234"), "{}", message),
+        other => panic!("expected the address to be rejected, got {:?}", other),
+    }
+}
+
+#[test]
+fn reports_the_chains_whose_verification_panicked() {
+    let _project = Project::new("verify-panic");
+    let toolchain = FakeToolchain::default().panic_on_verify("arbitrum-key");
+    let args: Vec<String> = ["script/DeployProtocol.s.sol", "--broadcast"].map(String::from).into();
+    for chain in ["sepolia", "arbitrum"] {
+        let mut args = args.clone();
+        args.extend(["--rpc-url".to_string(), chain.to_string()]);
+        toolchain.run_script(&args, &[]).unwrap();
+    }
+
+    let foundry = FoundryConfig::load("default").unwrap();
+    let chains = vec!["sepolia".to_string(), "arbitrum".to_string()];
+    let chain_ids = foundry.chain_ids(&chains, &toolchain).unwrap();
+    let settings = VerifySettings::default();
+    let verifier = Verifier::new(&toolchain, &foundry, &settings, false);
+
+    let failed = verifier.verify_all("DeployProtocol.s.sol", &BroadcastRun::Latest, &chains, &chain_ids);
+    assert_eq!(failed, ["the contracts on arbitrum"]);
+    let verified =
+        toolchain.verifications().iter().filter(|call| call.env["ETHERSCAN_API_KEY"] == "sepolia-key").count();
+    assert_eq!(verified, 2);
+}